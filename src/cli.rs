@@ -0,0 +1,160 @@
+use anyhow::{anyhow, bail, Context};
+
+use crate::settings::WindowSettings;
+
+pub const USAGE: &str = "\
+Usage: gameclockbone [OPTIONS]
+
+Options:
+    --fullscreen       Open the window fullscreen
+    --windowed         Open the window in the size of the settings
+    --kiosk            Fullscreen without decorations, resizing nor cursor
+    --size WxH         Set the size of the window, e.g. 1280x720
+    --scale FACTOR     Set the scale factor of the interface, e.g. 1.5
+    -h, --help         Print this message
+
+The options override the window settings for this run only.";
+
+/// The options given on the command line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    pub help: bool,
+    pub fullscreen: Option<bool>,
+    pub kiosk: bool,
+    pub size: Option<(u32, u32)>,
+    pub scale: Option<f64>,
+}
+
+impl Options {
+    /// Parses the arguments following the name of the program.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => options.help = true,
+                "--fullscreen" => options.fullscreen = Some(true),
+                "--windowed" => options.fullscreen = Some(false),
+                "--kiosk" => options.kiosk = true,
+                "--size" => {
+                    let value = args.next().ok_or_else(|| anyhow!("--size needs a value"))?;
+                    options.size = Some(parse_size(&value)?);
+                }
+                "--scale" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--scale needs a value"))?;
+                    options.scale = Some(parse_scale(&value)?);
+                }
+                _ => bail!("unknown option {}", arg),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Overrides `window` with the options given.
+    pub fn apply(&self, window: &mut WindowSettings) {
+        if let Some(fullscreen) = self.fullscreen {
+            window.fullscreen = fullscreen;
+            // Asking for a window explicitly leaves the kiosk mode of the settings.
+            if !fullscreen {
+                window.kiosk = false;
+            }
+        }
+        if self.kiosk {
+            window.kiosk = true;
+        }
+        if let Some((width, height)) = self.size {
+            window.width = width;
+            window.height = height;
+        }
+        if self.scale.is_some() {
+            window.scale = self.scale;
+        }
+    }
+}
+
+fn parse_size(value: &str) -> anyhow::Result<(u32, u32)> {
+    let mut parts = value.splitn(2, &['x', 'X'][..]);
+    let size = match (parts.next(), parts.next()) {
+        (Some(width), Some(height)) => (width.trim().parse::<u32>(), height.trim().parse::<u32>()),
+        _ => bail!("invalid size {}, expected e.g. 1280x720", value),
+    };
+    match size {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => bail!("invalid size {}, expected e.g. 1280x720", value),
+    }
+}
+
+fn parse_scale(value: &str) -> anyhow::Result<f64> {
+    let scale = value
+        .parse::<f64>()
+        .with_context(|| format!("invalid scale factor {}", value))?;
+    if scale.is_finite() && scale > 0.0 {
+        Ok(scale)
+    } else {
+        bail!("invalid scale factor {}, expected a positive number", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Options> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_options() {
+        assert_eq!(parse(&[]).unwrap(), Options::default());
+        assert_eq!(
+            parse(&["--kiosk", "--size", "1280x720", "--scale", "1.5"]).unwrap(),
+            Options {
+                kiosk: true,
+                size: Some((1280, 720)),
+                scale: Some(1.5),
+                ..Options::default()
+            }
+        );
+        assert_eq!(parse(&["--windowed"]).unwrap().fullscreen, Some(false));
+        assert_eq!(parse(&["--fullscreen"]).unwrap().fullscreen, Some(true));
+        assert!(parse(&["-h"]).unwrap().help);
+        assert_eq!(
+            parse(&["--size", "800X600"]).unwrap().size,
+            Some((800, 600))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        for args in &[
+            &["--full"][..],
+            &["--size"],
+            &["--size", "1280"],
+            &["--size", "0x720"],
+            &["--size", "axb"],
+            &["--scale"],
+            &["--scale", "0"],
+            &["--scale", "-1"],
+            &["--scale", "inf"],
+            &["--scale", "NaN"],
+        ] {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn overrides_the_settings() {
+        let mut window = WindowSettings {
+            kiosk: true,
+            ..WindowSettings::default()
+        };
+        parse(&["--windowed", "--size", "800x600"])
+            .unwrap()
+            .apply(&mut window);
+        assert!(!window.fullscreen);
+        assert!(!window.kiosk);
+        assert_eq!((window.width, window.height), (800, 600));
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{wall_clock, Player, Snapshot};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Correction {
+    Add(Duration),
+    Subtract(Duration),
+    Set(Duration),
+    SetMoves(u32),
+}
+
+impl Correction {
+    /// Applies this correction to a remaining time; the move counter is left as it is.
+    pub fn apply(self, remaining: Duration) -> Duration {
+        match self {
+            Correction::Add(duration) => remaining + duration,
+            Correction::Subtract(duration) => remaining
+                .checked_sub(duration)
+                .unwrap_or_else(|| Duration::new(0, 0)),
+            Correction::Set(duration) => duration,
+            Correction::SetMoves(_) => remaining,
+        }
+    }
+}
+
+/// A correction of a clock made by an arbiter.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Adjustment {
+    pub player: Player,
+    pub correction: Correction,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AdjustmentRecord {
+    pub adjustment: Adjustment,
+    #[serde(with = "wall_clock")]
+    pub at: Instant,
+    pub before: Snapshot,
+    pub after: Snapshot,
+}
@@ -0,0 +1,361 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{ClockSettings, Overtime};
+
+use super::Timer;
+
+/// The progress of a player through the overtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum OvertimeState {
+    /// The number of periods left including the current one.
+    ByoYomi { periods: u32 },
+    /// The number of moves left to be made in the current block.
+    Canadian { moves: u32 },
+}
+
+/// The clock of a player following a time control.
+///
+/// The timer counts down the main time first and then the current overtime period or block.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerClock {
+    timer: Timer,
+    stage: usize,
+    /// The number of moves made in the current stage.
+    stage_moves: u32,
+    overtime: Option<OvertimeState>,
+    /// The instant the clock was last started, which the timer forgets when a budget runs out.
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl PlayerClock {
+    pub fn new(settings: &ClockSettings) -> Self {
+        Self {
+            timer: Timer::new(settings.time_limit()),
+            stage: 0,
+            stage_moves: 0,
+            overtime: None,
+            started: None,
+        }
+    }
+
+    /// Returns the time left in the current budget, i.e. the main time or the overtime period.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.timer.remaining(now)
+    }
+
+    pub fn overtime(&self) -> Option<OvertimeState> {
+        self.overtime
+    }
+
+    /// Returns the index of the current stage of the main time.
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    /// Starts a new move, which grants the delay of the current stage.
+    pub fn start_move(&mut self, settings: &ClockSettings, at: Instant) {
+        let delay = match (self.overtime, settings.stage(self.stage)) {
+            (None, Some(stage)) => stage.delay,
+            _ => Duration::new(0, 0),
+        };
+        self.timer.set_delay(delay);
+        self.start(at);
+    }
+
+    /// Starts the clock again in the middle of a move.
+    pub fn start(&mut self, at: Instant) {
+        self.timer.start(at);
+        if self.started.is_none() {
+            self.started = Some(at);
+        }
+    }
+
+    /// Stops the clock and returns the time elapsed since it was started.
+    pub fn stop(&mut self, at: Instant) -> Duration {
+        self.timer.stop(at);
+        self.started
+            .take()
+            .map(|started| at.saturating_duration_since(started))
+            .unwrap_or_else(|| Duration::new(0, 0))
+    }
+
+    /// Moves on to the next budgets which have run out by `now`.
+    ///
+    /// Returns the instant the player has run out of time at if there are no more budgets.
+    pub fn settle(&mut self, settings: &ClockSettings, now: Instant) -> Option<Instant> {
+        while let Some(deadline) = self.timer.deadline().filter(|&deadline| deadline <= now) {
+            self.timer.stop(deadline);
+            match (self.overtime, settings.overtime) {
+                (None, Some(Overtime::ByoYomi { periods, time })) if periods > 0 => {
+                    info!("The main time has run out; byo-yomi starts.");
+                    self.overtime = Some(OvertimeState::ByoYomi { periods });
+                    self.timer = Timer::new(time);
+                }
+                (None, Some(Overtime::Canadian { moves, time })) if moves > 0 => {
+                    info!("The main time has run out; Canadian overtime starts.");
+                    self.overtime = Some(OvertimeState::Canadian { moves });
+                    self.timer = Timer::new(time);
+                }
+                (
+                    Some(OvertimeState::ByoYomi { periods }),
+                    Some(Overtime::ByoYomi { time, .. }),
+                ) if periods > 1 => {
+                    info!("A byo-yomi period has been consumed.");
+                    self.overtime = Some(OvertimeState::ByoYomi {
+                        periods: periods - 1,
+                    });
+                    self.timer = Timer::new(time);
+                }
+                _ => return Some(deadline),
+            }
+            self.timer.start(deadline);
+        }
+        None
+    }
+
+    /// Ends a move at `at` and returns the time elapsed since the clock was last started.
+    ///
+    /// The clock has to be settled at `at` beforehand.
+    pub fn end_move(&mut self, settings: &ClockSettings, at: Instant) -> Duration {
+        let elapsed = self.stop(at);
+        match (self.overtime, settings.overtime) {
+            (None, _) => {
+                if let Some(stage) = settings.stage(self.stage) {
+                    self.timer.add(stage.increment);
+                    self.stage_moves += 1;
+                    if Some(self.stage_moves) == stage.moves {
+                        self.stage += 1;
+                        self.stage_moves = 0;
+                        if let Some(next) = settings.stage(self.stage) {
+                            info!("Stage {} has started.", self.stage + 1);
+                            self.timer.add(next.time);
+                        }
+                    }
+                }
+            }
+            (Some(OvertimeState::ByoYomi { .. }), Some(Overtime::ByoYomi { time, .. })) => {
+                // A period isn't consumed when the move is made in time.
+                self.timer = Timer::new(time);
+            }
+            (
+                Some(OvertimeState::Canadian { moves }),
+                Some(Overtime::Canadian { moves: total, time }),
+            ) => {
+                if moves > 1 {
+                    self.overtime = Some(OvertimeState::Canadian { moves: moves - 1 });
+                } else {
+                    info!("A Canadian overtime block has been completed.");
+                    self.overtime = Some(OvertimeState::Canadian { moves: total });
+                    self.timer = Timer::new(time);
+                }
+            }
+            _ => {}
+        }
+        elapsed
+    }
+
+    /// Adds `duration` to the time left in the current budget.
+    pub fn add(&mut self, duration: Duration) {
+        self.timer.add(duration);
+    }
+
+    /// Overwrites the time left in the current budget.
+    pub fn set_remaining(&mut self, remaining: Duration, at: Instant) {
+        self.timer.set_remaining(remaining, at);
+    }
+
+    /// Returns a stopped copy of this clock as of `at`.
+    pub fn frozen(&self, at: Instant) -> Self {
+        let mut clock = self.clone();
+        clock.stop(at);
+        clock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Stage;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn settings(stages: Vec<Stage>, overtime: Option<Overtime>) -> ClockSettings {
+        ClockSettings {
+            stages,
+            overtime,
+            hourglass: false,
+        }
+    }
+
+    /// Plays a move from `from` to `to` seconds after `start` and returns its duration.
+    fn play(
+        clock: &mut PlayerClock,
+        settings: &ClockSettings,
+        start: Instant,
+        from: u64,
+        to: u64,
+    ) -> Duration {
+        clock.start_move(settings, start + secs(from));
+        assert_eq!(clock.settle(settings, start + secs(to)), None);
+        clock.end_move(settings, start + secs(to))
+    }
+
+    #[test]
+    fn repeats_the_last_stage_with_moves() {
+        let start = Instant::now();
+        let settings = settings(
+            vec![Stage {
+                moves: Some(2),
+                ..Stage::new(secs(60))
+            }],
+            None,
+        );
+        let mut clock = PlayerClock::new(&settings);
+        assert_eq!(play(&mut clock, &settings, start, 0, 10), secs(10));
+        assert_eq!(clock.stage(), 0);
+        assert_eq!(clock.remaining(start), secs(50));
+        play(&mut clock, &settings, start, 20, 25);
+        assert_eq!(clock.stage(), 1);
+        assert_eq!(clock.remaining(start), secs(105));
+        play(&mut clock, &settings, start, 30, 40);
+        play(&mut clock, &settings, start, 50, 55);
+        assert_eq!(clock.stage(), 2);
+        assert_eq!(clock.remaining(start), secs(150));
+    }
+
+    #[test]
+    fn ends_with_a_stage_without_moves() {
+        let start = Instant::now();
+        let settings = settings(
+            vec![
+                Stage {
+                    moves: Some(1),
+                    ..Stage::new(secs(60))
+                },
+                Stage::new(secs(30)),
+            ],
+            None,
+        );
+        let mut clock = PlayerClock::new(&settings);
+        play(&mut clock, &settings, start, 0, 10);
+        assert_eq!(clock.stage(), 1);
+        assert_eq!(clock.remaining(start), secs(80));
+        play(&mut clock, &settings, start, 20, 30);
+        assert_eq!(clock.stage(), 1);
+        assert_eq!(clock.remaining(start), secs(70));
+
+        clock.start_move(&settings, start + secs(40));
+        assert_eq!(
+            clock.settle(&settings, start + secs(200)),
+            Some(start + secs(110))
+        );
+    }
+
+    #[test]
+    fn adds_the_fischer_increment() {
+        let start = Instant::now();
+        let settings = settings(
+            vec![Stage {
+                increment: secs(5),
+                ..Stage::new(secs(60))
+            }],
+            None,
+        );
+        let mut clock = PlayerClock::new(&settings);
+        play(&mut clock, &settings, start, 0, 10);
+        assert_eq!(clock.remaining(start), secs(55));
+        play(&mut clock, &settings, start, 20, 22);
+        assert_eq!(clock.remaining(start), secs(58));
+    }
+
+    #[test]
+    fn grants_the_delay_once_per_move() {
+        let start = Instant::now();
+        let settings = settings(
+            vec![Stage {
+                delay: secs(5),
+                ..Stage::new(secs(60))
+            }],
+            None,
+        );
+        let mut clock = PlayerClock::new(&settings);
+        assert_eq!(play(&mut clock, &settings, start, 0, 3), secs(3));
+        assert_eq!(clock.remaining(start), secs(60));
+        play(&mut clock, &settings, start, 10, 18);
+        assert_eq!(clock.remaining(start), secs(57));
+
+        // A pause in the middle of the delay keeps what is left of it.
+        clock.start_move(&settings, start + secs(20));
+        clock.stop(start + secs(22));
+        clock.start(start + secs(30));
+        assert_eq!(clock.remaining(start + secs(33)), secs(57));
+        assert_eq!(clock.remaining(start + secs(35)), secs(55));
+        assert_eq!(
+            clock.settle(&settings, start + secs(100)),
+            Some(start + secs(90))
+        );
+    }
+
+    #[test]
+    fn consumes_byo_yomi_periods() {
+        let start = Instant::now();
+        let settings = settings(
+            vec![Stage::new(secs(10))],
+            Some(Overtime::ByoYomi {
+                periods: 2,
+                time: secs(5),
+            }),
+        );
+        let mut clock = PlayerClock::new(&settings);
+        assert_eq!(play(&mut clock, &settings, start, 0, 12), secs(12));
+        assert_eq!(
+            clock.overtime(),
+            Some(OvertimeState::ByoYomi { periods: 2 })
+        );
+        assert_eq!(clock.remaining(start), secs(5));
+
+        clock.start_move(&settings, start + secs(20));
+        assert_eq!(clock.settle(&settings, start + secs(26)), None);
+        assert_eq!(
+            clock.overtime(),
+            Some(OvertimeState::ByoYomi { periods: 1 })
+        );
+        assert_eq!(clock.remaining(start + secs(26)), secs(4));
+        assert_eq!(
+            clock.settle(&settings, start + secs(31)),
+            Some(start + secs(30))
+        );
+        assert_eq!(clock.remaining(start + secs(31)), secs(0));
+    }
+
+    #[test]
+    fn renews_canadian_blocks() {
+        let start = Instant::now();
+        let settings = settings(
+            vec![Stage::new(secs(10))],
+            Some(Overtime::Canadian {
+                moves: 2,
+                time: secs(20),
+            }),
+        );
+        let mut clock = PlayerClock::new(&settings);
+        play(&mut clock, &settings, start, 0, 15);
+        assert_eq!(clock.overtime(), Some(OvertimeState::Canadian { moves: 1 }));
+        assert_eq!(clock.remaining(start), secs(15));
+        play(&mut clock, &settings, start, 20, 25);
+        assert_eq!(clock.overtime(), Some(OvertimeState::Canadian { moves: 2 }));
+        assert_eq!(clock.remaining(start), secs(20));
+
+        clock.start_move(&settings, start + secs(30));
+        assert_eq!(
+            clock.settle(&settings, start + secs(51)),
+            Some(start + secs(50))
+        );
+    }
+}
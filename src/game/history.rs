@@ -0,0 +1,225 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{wall_clock, AdjustmentRecord, GameState, Player, PlayerClock};
+
+/// The state of a game at an instant, used to revert a press.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub state: GameState,
+    pub remaining: Vec<Duration>,
+    pub moves: Vec<u32>,
+    /// The stopped clocks of the players.
+    pub clocks: Vec<PlayerClock>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Press {
+    pub player: Player,
+    #[serde(with = "wall_clock")]
+    pub at: Instant,
+    /// The time used by the player whose clock has been stopped by this press.
+    pub elapsed: Duration,
+    pub before: Snapshot,
+    pub after: Snapshot,
+}
+
+impl Press {
+    /// Returns whether this press has ended a move, i.e. it hasn't just started the game.
+    pub fn is_move(&self) -> bool {
+        matches!(self.before.state, GameState::Running(_))
+    }
+}
+
+/// The end of the last move of a player who has run out of time.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FlagFall {
+    pub player: Player,
+    #[serde(with = "wall_clock")]
+    pub at: Instant,
+    pub elapsed: Duration,
+    /// The time left to the players when the flag has fallen.
+    #[serde(default)]
+    pub remaining: Vec<Duration>,
+}
+
+/// A suspension of the game.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Pause {
+    /// The player to move.
+    pub player: Player,
+    #[serde(with = "wall_clock")]
+    pub at: Instant,
+    /// The time the game has been suspended for, or `None` while it still is.
+    pub duration: Option<Duration>,
+    pub remaining: Vec<Duration>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    presses: Vec<Press>,
+    undone: Vec<Press>,
+    adjustments: Vec<AdjustmentRecord>,
+    pauses: Vec<Pause>,
+    /// The number of presses which can't be undone any more.
+    sealed: usize,
+    flag_fall: Option<FlagFall>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn presses(&self) -> &[Press] {
+        &self.presses
+    }
+
+    /// Returns the presses which have ended a move of `player`.
+    pub fn moves(&self, player: Player) -> impl Iterator<Item = &Press> {
+        self.presses
+            .iter()
+            .filter(move |press| press.player == player && press.is_move())
+    }
+
+    pub fn flag_fall(&self) -> Option<&FlagFall> {
+        self.flag_fall.as_ref()
+    }
+
+    pub fn set_flag_fall(&mut self, flag_fall: Option<FlagFall>) {
+        self.flag_fall = flag_fall;
+    }
+
+    pub fn adjustments(&self) -> &[AdjustmentRecord] {
+        &self.adjustments
+    }
+
+    pub fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+
+    pub fn push_pause(&mut self, pause: Pause) {
+        self.pauses.push(pause);
+    }
+
+    /// Ends the suspension still going on, if any, at `at`.
+    pub fn end_pause(&mut self, at: Instant) {
+        if let Some(pause) = self
+            .pauses
+            .last_mut()
+            .filter(|pause| pause.duration.is_none())
+        {
+            pause.duration = Some(at.saturating_duration_since(pause.at));
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.presses.len() > self.sealed
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Records a new press, which discards the presses that could be redone.
+    pub fn push(&mut self, press: Press) {
+        self.presses.push(press);
+        self.undone.clear();
+    }
+
+    /// Records a correction by an arbiter.
+    ///
+    /// Reverting a press would silently discard the correction, so the presses made so far can't
+    /// be undone after it.
+    pub fn push_adjustment(&mut self, record: AdjustmentRecord) {
+        self.adjustments.push(record);
+        self.undone.clear();
+        self.sealed = self.presses.len();
+    }
+
+    pub fn undo(&mut self) -> Option<&Press> {
+        if !self.can_undo() {
+            return None;
+        }
+        let press = self.presses.pop()?;
+        self.undone.push(press);
+        self.undone.last()
+    }
+
+    pub fn redo(&mut self) -> Option<&Press> {
+        let press = self.undone.pop()?;
+        self.presses.push(press);
+        self.presses.last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(player: Player, at: Instant) -> Press {
+        let snapshot = |state| Snapshot {
+            state,
+            remaining: Vec::new(),
+            moves: Vec::new(),
+            clocks: Vec::new(),
+        };
+        Press {
+            player,
+            at,
+            elapsed: Duration::new(0, 0),
+            before: snapshot(GameState::Running(player)),
+            after: snapshot(GameState::Running(player)),
+        }
+    }
+
+    fn times(presses: &[Press], start: Instant) -> Vec<u64> {
+        presses
+            .iter()
+            .map(|press| press.at.duration_since(start).as_secs())
+            .collect()
+    }
+
+    #[test]
+    fn undoes_and_redoes_in_order() {
+        let start = Instant::now();
+        let mut history = History::new();
+        assert!(!history.can_undo());
+        assert!(history.undo().is_none());
+        for secs in 1..=3 {
+            history.push(press(Player::First, start + Duration::from_secs(secs)));
+        }
+
+        let undone = history.undo().unwrap().at;
+        assert_eq!(undone, start + Duration::from_secs(3));
+        let undone = history.undo().unwrap().at;
+        assert_eq!(undone, start + Duration::from_secs(2));
+        assert_eq!(times(history.presses(), start), vec![1]);
+        assert!(history.can_redo());
+
+        let redone = history.redo().unwrap().at;
+        assert_eq!(redone, start + Duration::from_secs(2));
+        assert_eq!(times(history.presses(), start), vec![1, 2]);
+        let redone = history.redo().unwrap().at;
+        assert_eq!(redone, start + Duration::from_secs(3));
+        assert!(!history.can_redo());
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn discards_the_undone_presses_on_a_new_one() {
+        let start = Instant::now();
+        let mut history = History::new();
+        history.push(press(Player::First, start + Duration::from_secs(1)));
+        history.push(press(Player::Second, start + Duration::from_secs(2)));
+        history.undo();
+        history.undo();
+        assert!(!history.can_undo());
+
+        history.push(press(Player::Second, start + Duration::from_secs(3)));
+        assert!(!history.can_redo());
+        assert!(history.redo().is_none());
+        assert_eq!(times(history.presses(), start), vec![3]);
+    }
+}
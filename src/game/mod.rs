@@ -0,0 +1,513 @@
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{ClockSettings, Players, PressSettings, Settings};
+
+mod adjustment;
+mod clock;
+mod history;
+mod state;
+mod stats;
+mod timer;
+
+pub use adjustment::{Adjustment, AdjustmentRecord, Correction};
+pub use clock::{OvertimeState, PlayerClock};
+pub use history::{FlagFall, History, Pause, Press, Snapshot};
+pub use state::{GameState, Player};
+pub use stats::PlayerStats;
+pub use timer::Timer;
+pub use wall_clock::system_time;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Game {
+    clock: ClockSettings,
+    players: Players,
+    #[serde(default)]
+    press: PressSettings,
+    /// The time left below which a player is in time trouble, if not a tenth of the time limit.
+    #[serde(default)]
+    time_trouble: Option<Duration>,
+    clocks: Vec<PlayerClock>,
+    moves: Vec<u32>,
+    state: GameState,
+    /// The time spent on the current move before the game was last paused.
+    spent: Duration,
+    history: History,
+}
+
+impl Game {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            clock: settings.clock.clone(),
+            players: settings.players,
+            press: settings.press.clone(),
+            time_trouble: settings.display.time_trouble,
+            clocks: (0..settings.players.count())
+                .map(|_| PlayerClock::new(&settings.clock))
+                .collect(),
+            moves: vec![0; settings.players.count()],
+            state: GameState::Ready,
+            spent: Duration::new(0, 0),
+            history: History::new(),
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn players(&self) -> &'static [Player] {
+        let all: &'static [Player] = &Player::ALL;
+        &all[..self.players.count()]
+    }
+
+    pub fn clock_settings(&self) -> &ClockSettings {
+        &self.clock
+    }
+
+    pub fn time_limit(&self) -> Duration {
+        self.clock.time_limit()
+    }
+
+    /// Returns the time left to `player` in the current budget, i.e. the main time or the
+    /// overtime period.
+    pub fn remaining(&self, player: Player, now: Instant) -> Duration {
+        self.clocks[player.index()].remaining(now)
+    }
+
+    pub fn overtime(&self, player: Player) -> Option<OvertimeState> {
+        self.clocks[player.index()].overtime()
+    }
+
+    pub fn stage(&self, player: Player) -> usize {
+        self.clocks[player.index()].stage()
+    }
+
+    /// Returns the number of moves made by `player`, where a move is a press stopping their clock.
+    pub fn moves(&self, player: Player) -> u32 {
+        self.moves[player.index()]
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    fn snapshot(&self, at: Instant) -> Snapshot {
+        Snapshot {
+            state: self.state,
+            remaining: self
+                .clocks
+                .iter()
+                .map(|clock| clock.remaining(at))
+                .collect(),
+            moves: self.moves.clone(),
+            clocks: self.clocks.iter().map(|clock| clock.frozen(at)).collect(),
+        }
+    }
+
+    /// Restores `snapshot`, restarting the clock to move at `at`.
+    ///
+    /// A paused or ready game is paused rather than running afterwards so that it is never started
+    /// by surprise.
+    fn restore(&mut self, snapshot: &Snapshot, at: Instant) {
+        self.clocks = snapshot.clocks.clone();
+        self.moves = snapshot.moves.clone();
+        self.spent = Duration::new(0, 0);
+        self.state = match (self.state, snapshot.state) {
+            (GameState::Paused(_), GameState::Running(player))
+            | (GameState::Ready, GameState::Running(player)) => GameState::Paused(player),
+            (_, state) => state,
+        };
+        if let GameState::Running(player) = self.state {
+            self.clocks[player.index()].start(at);
+        }
+    }
+
+    /// Settles the running clock at `at`, finishing the game if its player has run out of time.
+    ///
+    /// Returns `false` if the game has been finished.
+    fn settle(&mut self, at: Instant) -> bool {
+        if let GameState::Running(current) = self.state {
+            let clock = &mut self.clocks[current.index()];
+            if let Some(deadline) = clock.settle(&self.clock, at) {
+                let elapsed = self.spent + clock.stop(deadline);
+                let remaining = self
+                    .clocks
+                    .iter()
+                    .map(|clock| clock.remaining(deadline))
+                    .collect();
+                self.state = GameState::Finished(current);
+                self.history.set_flag_fall(Some(FlagFall {
+                    player: current,
+                    at: deadline,
+                    elapsed,
+                    remaining,
+                }));
+                info!("{:?} has run out of time.", current);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Handles a press of the clock by `player` at the instant `at`.
+    ///
+    /// Returns `false` if a press isn't valid in the current state, if it has come after the
+    /// flag fall of the player to move, or if it is rejected by the press settings.
+    pub fn press(&mut self, player: Player, at: Instant) -> bool {
+        if let Some(last) = self.history.presses().last() {
+            let since = at.saturating_duration_since(last.at);
+            if since < self.press.guard {
+                debug!(
+                    "Ignore a press by {:?} {:?} after the last one.",
+                    player, since
+                );
+                return false;
+            }
+        }
+        if let GameState::Running(current) = self.state {
+            if self.press.strict && player != current {
+                debug!(
+                    "Ignore a press by {:?} while {:?} is to move.",
+                    player, current
+                );
+                return false;
+            }
+        }
+        if !self.settle(at) {
+            return false;
+        }
+        let before = self.snapshot(at);
+        let (player, elapsed) = match self.state {
+            GameState::Ready => {
+                let next = player.opponent(self.players);
+                self.clocks[next.index()].start_move(&self.clock, at);
+                self.state = GameState::Running(next);
+                info!("The game has started ({:?} to move).", next);
+                (player, Duration::new(0, 0))
+            }
+            GameState::Running(current) => {
+                let elapsed = self.spent + self.clocks[current.index()].end_move(&self.clock, at);
+                self.spent = Duration::new(0, 0);
+                self.moves[current.index()] += 1;
+                let next = current.opponent(self.players);
+                if self.clock.hourglass && next != current {
+                    self.clocks[next.index()].add(elapsed);
+                }
+                self.clocks[next.index()].start_move(&self.clock, at);
+                self.state = GameState::Running(next);
+                debug!("{:?} has moved in {:?}.", current, elapsed);
+                (current, elapsed)
+            }
+            state => {
+                debug!("Ignore a press by {:?} in {:?}.", player, state);
+                return false;
+            }
+        };
+        let after = self.snapshot(at);
+        self.history.push(Press {
+            player,
+            at,
+            elapsed,
+            before,
+            after,
+        });
+        true
+    }
+
+    /// Reverts the last press, giving back the time used since then.
+    pub fn undo(&mut self, at: Instant) -> bool {
+        if !self.state.accepts_undo() {
+            debug!("Ignore an undo in {:?}.", self.state);
+            return false;
+        }
+        if let Some(press) = self.history.undo() {
+            let before = press.before.clone();
+            info!(
+                "Undo the press by {:?} made {:?} ago after {:?} (remaining: {:?}, state: {:?}).",
+                press.player,
+                at.saturating_duration_since(press.at),
+                press.elapsed,
+                before.remaining,
+                before.state
+            );
+            self.restore(&before, at);
+            true
+        } else {
+            debug!("There is no press to undo.");
+            false
+        }
+    }
+
+    pub fn redo(&mut self, at: Instant) -> bool {
+        if !self.state.accepts_redo() {
+            debug!("Ignore a redo in {:?}.", self.state);
+            return false;
+        }
+        if let Some(press) = self.history.redo() {
+            let after = press.after.clone();
+            info!(
+                "Redo the press by {:?} made {:?} ago after {:?} (remaining: {:?}, state: {:?}).",
+                press.player,
+                at.saturating_duration_since(press.at),
+                press.elapsed,
+                after.remaining,
+                after.state
+            );
+            self.restore(&after, at);
+            true
+        } else {
+            debug!("There is no press to redo.");
+            false
+        }
+    }
+
+    pub fn pause(&mut self, at: Instant) -> bool {
+        if !self.settle(at) {
+            return false;
+        }
+        if let GameState::Running(current) = self.state {
+            self.suspend(current, at);
+            info!("The game has been paused.");
+            true
+        } else {
+            debug!("Ignore a pause in {:?}.", self.state);
+            false
+        }
+    }
+
+    pub fn resume(&mut self, at: Instant) -> bool {
+        if let GameState::Paused(current) = self.state {
+            self.clocks[current.index()].start(at);
+            self.state = GameState::Running(current);
+            self.history.end_pause(at);
+            info!("The game has been resumed.");
+            true
+        } else {
+            debug!("Ignore a resume in {:?}.", self.state);
+            false
+        }
+    }
+
+    /// Returns a copy of this game suspended at `at`, which can be saved and resumed later.
+    ///
+    /// The game is paused as by `pause`, but without logging it, so an interrupted game shows up
+    /// as a suspension in the history once resumed.
+    pub fn suspended(&self, at: Instant) -> Self {
+        let mut game = self.clone();
+        if game.settle(at) {
+            if let GameState::Running(current) = game.state {
+                game.suspend(current, at);
+            }
+        }
+        game
+    }
+
+    /// Stops the running clock of `current` and records the suspension.
+    fn suspend(&mut self, current: Player, at: Instant) {
+        self.spent += self.clocks[current.index()].stop(at);
+        self.state = GameState::Paused(current);
+        let remaining = self.snapshot(at).remaining;
+        self.history.push_pause(Pause {
+            player: current,
+            at,
+            duration: None,
+            remaining,
+        });
+    }
+
+    /// Checks whether the running clock has run out at `now`.
+    pub fn tick(&mut self, now: Instant) {
+        self.settle(now);
+    }
+
+    /// Ends the game by agreement, resignation or the like while the game is suspended or going
+    /// on.
+    pub fn finish(&mut self, at: Instant) -> bool {
+        if !self.settle(at) {
+            return false;
+        }
+        match self.state {
+            GameState::Running(current) | GameState::Paused(current) => {
+                self.spent += self.clocks[current.index()].stop(at);
+                self.state = GameState::Finished(current);
+                self.history.end_pause(at);
+                info!("The game has been ended ({:?} to move).", current);
+                true
+            }
+            state => {
+                debug!("Ignore a finish in {:?}.", state);
+                false
+            }
+        }
+    }
+
+    /// Computes the statistics of each player over the completed moves.
+    ///
+    /// A player is considered in time trouble below the threshold of the settings, a tenth of
+    /// the time limit by default.
+    pub fn stats(&self) -> Vec<PlayerStats> {
+        let threshold = self.time_trouble.unwrap_or_else(|| self.time_limit() / 10);
+        self.players()
+            .iter()
+            .map(|&player| {
+                let moves = self
+                    .history
+                    .moves(player)
+                    .map(|press| (press.elapsed, press.before.remaining[player.index()]));
+                let flag_fall = self
+                    .history
+                    .flag_fall()
+                    .filter(|flag_fall| flag_fall.player == player)
+                    .map(|flag_fall| flag_fall.elapsed);
+                PlayerStats::new(moves, flag_fall, threshold)
+            })
+            .collect()
+    }
+
+    /// Applies a correction by an arbiter and records it.
+    pub fn adjust(&mut self, adjustment: Adjustment, at: Instant) -> bool {
+        if !self.state.accepts_adjustment() {
+            debug!("Ignore {:?} in {:?}.", adjustment, self.state);
+            return false;
+        }
+        let before = self.snapshot(at);
+        let player = adjustment.player;
+        match adjustment.correction {
+            Correction::SetMoves(moves) => {
+                self.moves[player.index()] = moves;
+            }
+            correction => {
+                let clock = &mut self.clocks[player.index()];
+                let remaining = correction.apply(clock.remaining(at));
+                clock.set_remaining(remaining, at);
+                if self.state == GameState::Finished(player) && remaining > Duration::new(0, 0) {
+                    self.state = GameState::Paused(player);
+                    self.history.set_flag_fall(None);
+                }
+            }
+        }
+        let after = self.snapshot(at);
+        info!(
+            "The arbiter has corrected the clock of {:?} ({:?}, reason: {}): {:?} -> {:?}",
+            player, adjustment.correction, adjustment.reason, before, after
+        );
+        self.history.push_adjustment(AdjustmentRecord {
+            adjustment,
+            at,
+            before,
+            after,
+        });
+        true
+    }
+}
+
+/// (De)serializes an instant as the wall-clock time it corresponds to, since an `Instant` is
+/// meaningless in another process.
+///
+/// An instant which can't be represented in this process, e.g. one before the machine has booted,
+/// is loaded as the current one.
+mod wall_clock {
+    use std::time::{Instant, SystemTime};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Returns the wall-clock time a past `instant` corresponds to.
+    pub fn system_time(instant: Instant) -> SystemTime {
+        SystemTime::now() - Instant::now().saturating_duration_since(instant)
+    }
+
+    pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        system_time(*instant).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        let time = SystemTime::deserialize(deserializer)?;
+        let now = Instant::now();
+        Ok(SystemTime::now()
+            .duration_since(time)
+            .ok()
+            .and_then(|ago| now.checked_sub(ago))
+            .unwrap_or(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game of two players with 3 minutes each and no other time control.
+    fn game() -> Game {
+        Game::new(&Settings {
+            players: Players::Two,
+            ..Settings::default()
+        })
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn redoes_the_opening_press_as_paused() {
+        let start = Instant::now();
+        let mut game = game();
+        assert!(game.press(Player::First, start));
+        assert_eq!(game.state(), GameState::Running(Player::Second));
+
+        assert!(game.undo(start + secs(2)));
+        assert_eq!(game.state(), GameState::Ready);
+        assert!(game.history().can_redo());
+        assert!(game.state().accepts_redo());
+        assert!(!game.undo(start + secs(3)));
+
+        assert!(game.redo(start + secs(4)));
+        assert_eq!(game.state(), GameState::Paused(Player::Second));
+        assert_eq!(game.remaining(Player::Second, start + secs(10)), secs(180));
+        assert!(game.resume(start + secs(10)));
+        assert_eq!(game.remaining(Player::Second, start + secs(13)), secs(177));
+    }
+
+    #[test]
+    fn undoes_and_redoes_while_paused() {
+        let start = Instant::now();
+        let mut game = game();
+        game.press(Player::First, start);
+        game.press(Player::Second, start + secs(10));
+        assert!(game.pause(start + secs(15)));
+        assert_eq!(game.remaining(Player::First, start + secs(20)), secs(175));
+
+        assert!(game.undo(start + secs(20)));
+        assert_eq!(game.state(), GameState::Paused(Player::Second));
+        assert_eq!(game.moves(Player::Second), 0);
+        assert_eq!(game.remaining(Player::First, start + secs(30)), secs(180));
+        assert_eq!(game.remaining(Player::Second, start + secs(30)), secs(170));
+
+        assert!(game.redo(start + secs(30)));
+        assert_eq!(game.state(), GameState::Paused(Player::First));
+        assert_eq!(game.moves(Player::Second), 1);
+        assert!(game.resume(start + secs(40)));
+        assert_eq!(game.remaining(Player::First, start + secs(43)), secs(177));
+        assert_eq!(game.remaining(Player::Second, start + secs(43)), secs(170));
+    }
+
+    #[test]
+    fn refuses_undo_and_redo_after_a_flag_fall() {
+        let start = Instant::now();
+        let mut game = game();
+        game.press(Player::First, start);
+        game.press(Player::Second, start + secs(10));
+        game.undo(start + secs(20));
+        game.resume(start + secs(20));
+
+        game.tick(start + secs(200));
+        assert_eq!(game.state(), GameState::Finished(Player::Second));
+        assert_eq!(game.history().flag_fall().unwrap().at, start + secs(190));
+        assert!(!game.undo(start + secs(201)));
+        assert!(!game.redo(start + secs(201)));
+        assert_eq!(game.state(), GameState::Finished(Player::Second));
+        assert_eq!(game.history().presses().len(), 1);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Players;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Player {
+    First,
+    Second,
+}
+
+impl Player {
+    pub const ALL: [Player; 2] = [Player::First, Player::Second];
+
+    pub fn index(self) -> usize {
+        match self {
+            Player::First => 0,
+            Player::Second => 1,
+        }
+    }
+
+    /// Returns the player who moves after this player.
+    pub fn opponent(self, players: Players) -> Self {
+        match (players, self) {
+            (Players::One, _) => Player::First,
+            (Players::Two, Player::First) => Player::Second,
+            (Players::Two, Player::Second) => Player::First,
+        }
+    }
+}
+
+/// The lifecycle of a game.
+///
+/// | state        | press            | pause      | resume       | flag fall     | finish        |
+/// |--------------|------------------|------------|--------------|---------------|---------------|
+/// | `Ready`      | `Running(next)`  | -          | -            | -             | -             |
+/// | `Running(p)` | `Running(next)`  | `Paused(p)`| -            | `Finished(p)` | `Finished(p)` |
+/// | `Paused(p)`  | -                | -          | `Running(p)` | -             | `Finished(p)` |
+/// | `Finished(p)`| -                | -          | -            | -             | -             |
+///
+/// Any input not listed above is ignored. Undo and redo are accepted in `Running` and `Paused`,
+/// and redo also in `Ready` once the opening press has been undone. They restore the state
+/// recorded with the press, except that a paused or ready game becomes paused rather than
+/// running. An adjustment giving time back to the flagged player of a finished game makes it
+/// `Paused` again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GameState {
+    /// Waiting for the first press.
+    Ready,
+    /// The clock of the player is counting down.
+    Running(Player),
+    /// The game has been suspended while the player was to move.
+    Paused(Player),
+    /// The game has ended while the player was to move, by their flag fall or from the menu.
+    Finished(Player),
+}
+
+impl GameState {
+    pub fn accepts_press(self) -> bool {
+        match self {
+            GameState::Ready | GameState::Running(_) => true,
+            GameState::Paused(_) | GameState::Finished(_) => false,
+        }
+    }
+
+    pub fn accepts_pause(self) -> bool {
+        matches!(self, GameState::Running(_))
+    }
+
+    pub fn accepts_resume(self) -> bool {
+        matches!(self, GameState::Paused(_))
+    }
+
+    pub fn accepts_finish(self) -> bool {
+        match self {
+            GameState::Running(_) | GameState::Paused(_) => true,
+            GameState::Ready | GameState::Finished(_) => false,
+        }
+    }
+
+    pub fn is_finished(self) -> bool {
+        matches!(self, GameState::Finished(_))
+    }
+
+    /// Undo and redo revert presses, so they make sense only while the game goes on.
+    pub fn accepts_undo(self) -> bool {
+        match self {
+            GameState::Running(_) | GameState::Paused(_) => true,
+            GameState::Ready | GameState::Finished(_) => false,
+        }
+    }
+
+    /// Undoing the opening press goes back to `Ready`, from where it can still be redone.
+    pub fn accepts_redo(self) -> bool {
+        !matches!(self, GameState::Finished(_))
+    }
+
+    /// Arbiters may correct clocks in any state but `Running`; the game has to be paused first.
+    pub fn accepts_adjustment(self) -> bool {
+        !matches!(self, GameState::Running(_))
+    }
+
+    /// Returns the player whose clock is (or was, when suspended) counting down.
+    pub fn player_to_move(self) -> Option<Player> {
+        match self {
+            GameState::Ready => None,
+            GameState::Running(player)
+            | GameState::Paused(player)
+            | GameState::Finished(player) => Some(player),
+        }
+    }
+}
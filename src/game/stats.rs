@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+/// The time management of a player over a game.
+#[derive(Clone, Debug, Default)]
+pub struct PlayerStats {
+    pub moves: usize,
+    /// The time used including the last move of a player who has run out of time.
+    pub total: Duration,
+    pub average: Duration,
+    pub median: Duration,
+    pub longest: Duration,
+    /// The time used with less remaining time than the threshold.
+    pub time_trouble: Duration,
+}
+
+impl PlayerStats {
+    /// Computes the statistics from the time used and left at the end of each move.
+    pub fn new(
+        moves: impl Iterator<Item = (Duration, Duration)>,
+        flag_fall: Option<Duration>,
+        threshold: Duration,
+    ) -> Self {
+        let zero = Duration::new(0, 0);
+        let mut elapsed = Vec::new();
+        let mut time_trouble = zero;
+        for (used, remaining) in moves {
+            elapsed.push(used);
+            // Only the part of the move below the threshold counts as time trouble.
+            time_trouble += used.min(threshold.checked_sub(remaining).unwrap_or(zero));
+        }
+        let flag_fall = flag_fall.unwrap_or(zero);
+        time_trouble += flag_fall.min(threshold);
+
+        elapsed.sort();
+        let moves = elapsed.len();
+        let sum: Duration = elapsed.iter().sum();
+        let (average, median) = if moves == 0 {
+            (zero, zero)
+        } else if moves % 2 == 0 {
+            (
+                sum / moves as u32,
+                (elapsed[moves / 2 - 1] + elapsed[moves / 2]) / 2,
+            )
+        } else {
+            (sum / moves as u32, elapsed[moves / 2])
+        };
+
+        Self {
+            moves,
+            total: sum + flag_fall,
+            average,
+            median,
+            longest: elapsed.last().copied().unwrap_or(zero).max(flag_fall),
+            time_trouble,
+        }
+    }
+}
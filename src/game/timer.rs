@@ -0,0 +1,157 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// A countdown which is settled at the exact instants it is started and stopped.
+///
+/// The remaining time is never decremented by ticks; it is derived from the instant the timer was
+/// started, so the time charged to a player doesn't depend on how often the view is refreshed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Timer {
+    remaining: Duration,
+    /// The time which isn't charged after the timer is started.
+    delay: Duration,
+    /// An instant is meaningless in another process, so a timer is saved stopped.
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl Timer {
+    pub fn new(time_limit: Duration) -> Self {
+        Self {
+            remaining: time_limit,
+            delay: Duration::new(0, 0),
+            started: None,
+        }
+    }
+
+    pub fn start(&mut self, at: Instant) {
+        if self.started.is_none() {
+            self.started = Some(at);
+        }
+    }
+
+    /// Sets the time which isn't charged from now on. A stop consumes the part of it elapsed.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    fn charged(&self, now: Instant) -> Duration {
+        if let Some(started) = self.started {
+            now.saturating_duration_since(started)
+                .checked_sub(self.delay)
+                .unwrap_or_else(|| Duration::new(0, 0))
+        } else {
+            Duration::new(0, 0)
+        }
+    }
+
+    /// Stops the timer and returns the time elapsed since it was started.
+    pub fn stop(&mut self, at: Instant) -> Duration {
+        let charged = self.charged(at);
+        if let Some(started) = self.started.take() {
+            let elapsed = at.saturating_duration_since(started);
+            self.delay = self
+                .delay
+                .checked_sub(elapsed)
+                .unwrap_or_else(|| Duration::new(0, 0));
+            self.remaining = self
+                .remaining
+                .checked_sub(charged)
+                .unwrap_or_else(|| Duration::new(0, 0));
+            elapsed
+        } else {
+            Duration::new(0, 0)
+        }
+    }
+
+    /// Adds `duration` to the remaining time.
+    pub fn add(&mut self, duration: Duration) {
+        self.remaining = self.remaining.saturating_add(duration);
+    }
+
+    /// Overwrites the remaining time at `at`, keeping the timer running if it is.
+    pub fn set_remaining(&mut self, remaining: Duration, at: Instant) {
+        self.remaining = remaining;
+        if self.started.is_some() {
+            self.started = Some(at);
+        }
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.remaining
+            .checked_sub(self.charged(now))
+            .unwrap_or_else(|| Duration::new(0, 0))
+    }
+
+    /// Returns the instant the running timer will run out at, or `None` if it is stopped or the
+    /// instant is too far away to be represented.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.started?
+            .checked_add(self.delay)?
+            .checked_add(self.remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn counts_down_while_running() {
+        let start = Instant::now();
+        let mut timer = Timer::new(secs(60));
+        assert_eq!(timer.deadline(), None);
+        timer.start(start);
+        assert_eq!(timer.remaining(start + secs(10)), secs(50));
+        assert_eq!(timer.deadline(), Some(start + secs(60)));
+
+        assert_eq!(timer.stop(start + secs(15)), secs(15));
+        assert_eq!(timer.remaining(start + secs(30)), secs(45));
+        assert_eq!(timer.deadline(), None);
+        assert_eq!(timer.remaining(start + secs(90)), secs(45));
+    }
+
+    #[test]
+    fn charges_nothing_during_the_delay() {
+        let start = Instant::now();
+        let mut timer = Timer::new(secs(60));
+        timer.set_delay(secs(5));
+        timer.start(start);
+        assert_eq!(timer.remaining(start + secs(3)), secs(60));
+        assert_eq!(timer.remaining(start + secs(8)), secs(57));
+        assert_eq!(timer.deadline(), Some(start + secs(65)));
+        assert_eq!(timer.stop(start + secs(8)), secs(8));
+        assert_eq!(timer.remaining(start + secs(8)), secs(57));
+    }
+
+    #[test]
+    fn keeps_the_rest_of_the_delay_across_a_stop() {
+        let start = Instant::now();
+        let mut timer = Timer::new(secs(60));
+        timer.set_delay(secs(5));
+        timer.start(start);
+        timer.stop(start + secs(2));
+        assert_eq!(timer.remaining(start + secs(2)), secs(60));
+
+        timer.start(start + secs(10));
+        assert_eq!(timer.remaining(start + secs(13)), secs(60));
+        assert_eq!(timer.remaining(start + secs(15)), secs(58));
+        assert_eq!(timer.deadline(), Some(start + secs(73)));
+    }
+
+    #[test]
+    fn restarts_from_a_new_remaining_time() {
+        let start = Instant::now();
+        let mut timer = Timer::new(secs(60));
+        timer.start(start);
+        timer.set_remaining(secs(30), start + secs(10));
+        assert_eq!(timer.remaining(start + secs(20)), secs(20));
+        timer.add(secs(5));
+        assert_eq!(timer.deadline(), Some(start + secs(45)));
+    }
+}
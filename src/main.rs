@@ -1,6 +1,4 @@
-use anyhow;
-use iced::{Application, Settings};
-use log::{debug, trace, LevelFilter};
+use log::{debug, trace, warn, LevelFilter};
 use log4rs::{
     self,
     append::{
@@ -17,10 +15,17 @@ use log4rs::{
     Handle,
 };
 
+mod cli;
+mod game;
 mod pane;
+mod record;
+mod session;
 mod settings;
+mod shell;
+mod sound;
 
-use pane::RootPane;
+use cli::Options;
+use pane::{Flags, RootPane};
 
 fn setup_logger() -> anyhow::Result<Handle> {
     let log_dir = std::env::current_dir()?.join("log");
@@ -64,6 +69,18 @@ fn setup_logger() -> anyhow::Result<Handle> {
 }
 
 fn main() -> anyhow::Result<()> {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
     let _logger = setup_logger()?;
     trace!("Loggers have been set.");
 
@@ -71,7 +88,22 @@ fn main() -> anyhow::Result<()> {
         debug!("Debug mode!");
     }
 
-    RootPane::run(Settings::default());
-
-    Ok(())
+    let settings = settings::load();
+    let mut window = settings.window.clone();
+    options.apply(&mut window);
+    if let Some(scale) = window
+        .scale
+        .filter(|scale| !(scale.is_finite() && *scale > 0.0))
+    {
+        warn!("Ignore the invalid scale factor {}.", scale);
+        window.scale = None;
+    }
+    shell::run::<RootPane>(
+        Flags {
+            settings,
+            session: session::load(),
+            window: window.clone(),
+        },
+        &window,
+    )
 }
@@ -0,0 +1,326 @@
+use std::time::{Duration, Instant};
+
+use iced::{
+    button, text_input, Align, Button, Column, Container, Element, HorizontalAlignment, Length,
+    Radio, Row, Subscription, Text, TextInput, VerticalAlignment,
+};
+
+use crate::{
+    game::{Adjustment, Correction, Game, Player},
+    settings::{PinHash, Theme},
+};
+
+use super::{clock::format_duration, style};
+
+pub struct ArbiterPane {
+    pin: PinHash,
+    pin_input: text_input::State,
+    pin_value: String,
+    unlocked: bool,
+    player: Player,
+    hour_input: text_input::State,
+    hour_value: String,
+    min_input: text_input::State,
+    min_value: String,
+    sec_input: text_input::State,
+    sec_value: String,
+    moves_input: text_input::State,
+    moves_value: String,
+    reason_input: text_input::State,
+    reason_value: String,
+    unlock_button: button::State,
+    add_button: button::State,
+    subtract_button: button::State,
+    set_button: button::State,
+    set_moves_button: button::State,
+    back_button: button::State,
+}
+
+impl ArbiterPane {
+    /// Creates a pane which has to be unlocked with the PIN of `pin`.
+    pub fn new(pin: PinHash) -> Self {
+        Self {
+            pin,
+            pin_input: text_input::State::new(),
+            pin_value: String::new(),
+            unlocked: false,
+            player: Player::First,
+            hour_input: text_input::State::new(),
+            hour_value: String::new(),
+            min_input: text_input::State::new(),
+            min_value: String::new(),
+            sec_input: text_input::State::new(),
+            sec_value: String::new(),
+            moves_input: text_input::State::new(),
+            moves_value: String::new(),
+            reason_input: text_input::State::new(),
+            reason_value: String::new(),
+            unlock_button: button::State::new(),
+            add_button: button::State::new(),
+            subtract_button: button::State::new(),
+            set_button: button::State::new(),
+            set_moves_button: button::State::new(),
+            back_button: button::State::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: ArbiterMessage) {
+        match message {
+            ArbiterMessage::PinChanged(pin) => {
+                self.pin_value = pin;
+            }
+            ArbiterMessage::Unlock => {
+                if self.pin.verify(&self.pin_value) {
+                    self.unlocked = true;
+                }
+                self.pin_value.clear();
+            }
+            ArbiterMessage::PlayerSelected(player) => {
+                self.player = player;
+            }
+            ArbiterMessage::HourChanged(hour) => {
+                if hour.is_empty() || hour.trim().parse::<u8>().is_ok() {
+                    self.hour_value = hour;
+                }
+            }
+            ArbiterMessage::MinChanged(min) => {
+                if min.is_empty() || min.trim().parse::<u16>().is_ok() {
+                    self.min_value = min;
+                }
+            }
+            ArbiterMessage::SecChanged(sec) => {
+                if sec.is_empty() || sec.trim().parse::<u16>().is_ok() {
+                    self.sec_value = sec;
+                }
+            }
+            ArbiterMessage::MovesChanged(moves) => {
+                if moves.is_empty() || moves.trim().parse::<u32>().is_ok() {
+                    self.moves_value = moves;
+                }
+            }
+            ArbiterMessage::ReasonChanged(reason) => {
+                self.reason_value = reason;
+            }
+            ArbiterMessage::Adjust(_) => {
+                self.hour_value.clear();
+                self.min_value.clear();
+                self.sec_value.clear();
+                self.moves_value.clear();
+                self.reason_value.clear();
+            }
+            ArbiterMessage::Back => {}
+        }
+    }
+
+    pub fn view(&mut self, game: &Game, theme: &Theme) -> Element<'_, ArbiterMessage> {
+        let content = if self.unlocked {
+            self.adjustment_view(game, theme)
+        } else {
+            Column::new()
+                .push(Text::new("Enter the arbiter PIN"))
+                .push(
+                    TextInput::new(
+                        &mut self.pin_input,
+                        "PIN",
+                        &self.pin_value,
+                        ArbiterMessage::PinChanged,
+                    )
+                    .style(style::text_input(theme))
+                    .password()
+                    .on_submit(ArbiterMessage::Unlock),
+                )
+                .push(
+                    Row::new()
+                        .push(
+                            Button::new(&mut self.unlock_button, Text::new("Unlock"))
+                                .style(style::button(theme))
+                                .on_press(ArbiterMessage::Unlock),
+                        )
+                        .push(
+                            Button::new(&mut self.back_button, Text::new("Back"))
+                                .style(style::button(theme))
+                                .on_press(ArbiterMessage::Back),
+                        ),
+                )
+                .align_items(Align::Center)
+                .into()
+        };
+
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+
+    fn adjustment_view(&mut self, game: &Game, theme: &Theme) -> Element<'_, ArbiterMessage> {
+        // The clocks are stopped while this pane is shown, so any instant gives the same times.
+        let now = Instant::now();
+        let accepts_adjustment = game.state().accepts_adjustment();
+        let players = game.players().iter().fold(Row::new(), |row, &player| {
+            row.push(Radio::new(
+                player,
+                format!(
+                    "{:?} ({}, {} moves)",
+                    player,
+                    format_duration(game.remaining(player, now)),
+                    game.moves(player)
+                ),
+                Some(self.player),
+                ArbiterMessage::PlayerSelected,
+            ))
+        });
+
+        let duration = Duration::new(
+            self.hour_value.trim().parse::<u64>().unwrap_or(0) * (60 * 60)
+                + self.min_value.trim().parse::<u64>().unwrap_or(0) * 60
+                + self.sec_value.trim().parse::<u64>().unwrap_or(0),
+            0,
+        );
+        // Every correction has to be justified.
+        let reason = self.reason_value.trim().to_string();
+        let player = self.player;
+        let adjustment = |correction| {
+            ArbiterMessage::Adjust(Adjustment {
+                player,
+                correction,
+                reason: reason.clone(),
+            })
+        };
+        let mut add_button =
+            Button::new(&mut self.add_button, Text::new("Add")).style(style::button(theme));
+        let mut subtract_button = Button::new(&mut self.subtract_button, Text::new("Subtract"))
+            .style(style::button(theme));
+        let mut set_button =
+            Button::new(&mut self.set_button, Text::new("Set")).style(style::button(theme));
+        let mut set_moves_button = Button::new(&mut self.set_moves_button, Text::new("Set moves"))
+            .style(style::button(theme));
+        if accepts_adjustment && !reason.is_empty() {
+            add_button = add_button.on_press(adjustment(Correction::Add(duration)));
+            subtract_button = subtract_button.on_press(adjustment(Correction::Subtract(duration)));
+            set_button = set_button.on_press(adjustment(Correction::Set(duration)));
+            if let Ok(moves) = self.moves_value.trim().parse::<u32>() {
+                set_moves_button =
+                    set_moves_button.on_press(adjustment(Correction::SetMoves(moves)));
+            }
+        }
+
+        let records = game.history().adjustments().iter().rev().take(5).fold(
+            Column::new(),
+            |column, record| {
+                let index = record.adjustment.player.index();
+                column.push(Text::new(format!(
+                    "{:?}: {} ({} moves) -> {} ({} moves), {}, {} ago",
+                    record.adjustment.player,
+                    format_duration(record.before.remaining[index]),
+                    record.before.moves[index],
+                    format_duration(record.after.remaining[index]),
+                    record.after.moves[index],
+                    record.adjustment.reason,
+                    format_duration(now.saturating_duration_since(record.at))
+                )))
+            },
+        );
+
+        Column::new()
+            .push(
+                Text::new("Arbiter")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .vertical_alignment(VerticalAlignment::Center),
+            )
+            .push(players)
+            .push(
+                Row::new()
+                    .push(Text::new("time: ").vertical_alignment(VerticalAlignment::Center))
+                    .push(
+                        TextInput::new(
+                            &mut self.hour_input,
+                            "hour",
+                            &self.hour_value,
+                            ArbiterMessage::HourChanged,
+                        )
+                        .style(style::text_input(theme)),
+                    )
+                    .push(Text::new(":").vertical_alignment(VerticalAlignment::Center))
+                    .push(
+                        TextInput::new(
+                            &mut self.min_input,
+                            "minute",
+                            &self.min_value,
+                            ArbiterMessage::MinChanged,
+                        )
+                        .style(style::text_input(theme)),
+                    )
+                    .push(Text::new(":").vertical_alignment(VerticalAlignment::Center))
+                    .push(
+                        TextInput::new(
+                            &mut self.sec_input,
+                            "second",
+                            &self.sec_value,
+                            ArbiterMessage::SecChanged,
+                        )
+                        .style(style::text_input(theme)),
+                    ),
+            )
+            .push(
+                Row::new()
+                    .push(Text::new("moves: ").vertical_alignment(VerticalAlignment::Center))
+                    .push(
+                        TextInput::new(
+                            &mut self.moves_input,
+                            "moves",
+                            &self.moves_value,
+                            ArbiterMessage::MovesChanged,
+                        )
+                        .style(style::text_input(theme)),
+                    ),
+            )
+            .push(
+                Row::new()
+                    .push(Text::new("reason: ").vertical_alignment(VerticalAlignment::Center))
+                    .push(
+                        TextInput::new(
+                            &mut self.reason_input,
+                            "required",
+                            &self.reason_value,
+                            ArbiterMessage::ReasonChanged,
+                        )
+                        .style(style::text_input(theme)),
+                    ),
+            )
+            .push(
+                Row::new()
+                    .push(add_button)
+                    .push(subtract_button)
+                    .push(set_button)
+                    .push(set_moves_button)
+                    .push(
+                        Button::new(&mut self.back_button, Text::new("Back"))
+                            .style(style::button(theme))
+                            .on_press(ArbiterMessage::Back),
+                    ),
+            )
+            .push(records)
+            .align_items(Align::Center)
+            .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<ArbiterMessage> {
+        Subscription::none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ArbiterMessage {
+    PinChanged(String),
+    Unlock,
+    PlayerSelected(Player),
+    HourChanged(String),
+    MinChanged(String),
+    SecChanged(String),
+    MovesChanged(String),
+    ReasonChanged(String),
+    Adjust(Adjustment),
+    Back,
+}
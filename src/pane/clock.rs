@@ -1,91 +1,103 @@
 use std::{
     any::TypeId,
+    fs,
     hash::{Hash, Hasher},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::Mutex,
     time::{Duration, Instant},
 };
 
+use anyhow::{anyhow, bail, Context};
+
 use futures_timer::Delay;
 use iced::{
-    button, Align, Button, Color, Column, Container, Element, HorizontalAlignment, Length,
-    Subscription, Text, VerticalAlignment,
+    button, Align, Background, Button, Color, Column, Container, Element, Font,
+    HorizontalAlignment, Length, Row, Subscription, Text, VerticalAlignment,
 };
 use iced_futures::{
     futures::{stream, StreamExt},
     subscription::Recipe,
     BoxStream,
 };
+use rusttype::{point, Scale};
 
-pub struct Clock1PPane {
-    remaining: Duration,
+use crate::{
+    game::{Game, GameState, OvertimeState, Player},
+    settings::{DisplaySettings, Theme},
+};
+
+use super::style::{self, color};
+
+/// How long the colors of a fallen flag are swapped for when flashing.
+const FLASH_INTERVAL: Duration = Duration::from_millis(250);
+/// The height taken by the pause button and a status line below the digits.
+const CLOCK_RESERVED: u32 = 100;
+
+pub struct ClockPane {
+    display: DisplaySettings,
     interval: Duration,
-    previous: Instant,
-    toggle_button: button::State,
+    now: Instant,
+    press_buttons: [button::State; 2],
     pause_button: button::State,
-    running: bool,
 }
 
-impl Clock1PPane {
-    pub fn new(time_limit: Duration) -> Self {
+impl ClockPane {
+    pub fn new(display: &DisplaySettings) -> Self {
         Self {
-            remaining: time_limit,
+            display: display.clone(),
             interval: Duration::from_millis(10),
-            previous: Instant::now(),
-            toggle_button: button::State::new(),
+            now: Instant::now(),
+            press_buttons: [button::State::new(), button::State::new()],
             pause_button: button::State::new(),
-            running: false,
         }
     }
 
-    pub fn update(&mut self, message: Clock1PMessage) {
+    pub fn update(&mut self, message: ClockMessage) {
         match message {
-            Clock1PMessage::Pause => {
-                self.running = false;
-            }
-            Clock1PMessage::Tick(now) => {
-                if self.running {
-                    if now < self.previous {
-                        self.previous = now;
-                    } else {
-                        let dt = now - self.previous;
-                        self.previous = now;
-                        if self.remaining > dt {
-                            self.remaining -= dt;
-                        } else {
-                            self.remaining = Duration::new(0, 0);
-                        }
-                    }
+            ClockMessage::Tick(now) => {
+                // Ticks only refresh the view; the elapsed time is settled by the game itself.
+                if now > self.now {
+                    self.now = now;
                 }
             }
-            Clock1PMessage::Toggle => {
-                self.running = !self.running;
-                if self.running {
-                    self.previous = Instant::now();
-                }
+            _ => {
+                self.now = Instant::now();
             }
         }
     }
 
-    pub fn view(&mut self) -> Element<Clock1PMessage> {
-        let sec = self.remaining.as_secs();
-        let hour = sec / (60 * 60);
-        let sec = sec - hour * (60 * 60);
-        let min = sec / 60;
-        let sec = sec - min * 60;
-        Container::new(
-            Column::new()
-                .push(
+    pub fn view(
+        &mut self,
+        game: &Game,
+        theme: &Theme,
+        digits: &Digits,
+    ) -> Element<'_, ClockMessage> {
+        let now = self.now;
+        let display = &self.display;
+        let size = digits.size(game.players().len(), CLOCK_RESERVED);
+        let clocks = game
+            .players()
+            .iter()
+            .zip(self.press_buttons.iter_mut())
+            .fold(Row::new(), |row, (&player, state)| {
+                let remaining = game.remaining(player, now);
+                row.push(
                     Button::new(
-                        &mut self.toggle_button,
-                        Text::new(format!("{:02}:{:02}:{:02}", hour, min, sec))
-                            .width(Length::Fill)
-                            .horizontal_alignment(HorizontalAlignment::Center)
-                            .vertical_alignment(VerticalAlignment::Center),
+                        state,
+                        face(remaining, status(game, player), size, digits.font),
                     )
                     .width(Length::Fill)
                     .height(Length::Fill)
-                    .on_press(Clock1PMessage::Toggle)
-                    .style(ClockStyle),
+                    .on_press(ClockMessage::Press(player))
+                    .style(ClockStyle::new(
+                        theme, display, game, player, remaining, now,
+                    )),
                 )
+            });
+        Container::new(
+            Column::new()
+                .push(clocks.height(Length::Fill))
                 .push(
                     Button::new(
                         &mut self.pause_button,
@@ -94,7 +106,8 @@ impl Clock1PPane {
                             .horizontal_alignment(HorizontalAlignment::Center),
                     )
                     .width(Length::Fill)
-                    .on_press(Clock1PMessage::Pause),
+                    .on_press(ClockMessage::Pause)
+                    .style(style::button(theme)),
                 )
                 .align_items(Align::Center),
         )
@@ -105,16 +118,154 @@ impl Clock1PPane {
         .into()
     }
 
-    pub fn subscription(&self) -> Subscription<Clock1PMessage> {
-        Subscription::from_recipe(ClockRecipe(self.interval)).map(Clock1PMessage::Tick)
+    pub fn subscription(&self) -> Subscription<ClockMessage> {
+        ticks(self.interval).map(ClockMessage::Tick)
     }
 }
 
 #[derive(Clone, Debug)]
-pub enum Clock1PMessage {
+pub enum ClockMessage {
     Pause,
+    Press(Player),
     Tick(Instant),
-    Toggle,
+}
+
+/// Renders the time left to a player in digits of `size` above a line about their status.
+pub fn face<'a, M: 'a>(
+    remaining: Duration,
+    status: String,
+    size: u16,
+    font: Font,
+) -> Column<'a, M> {
+    Column::new()
+        .push(
+            Text::new(format_duration(remaining))
+                .size(size)
+                .font(font)
+                .width(Length::Fill)
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .vertical_alignment(VerticalAlignment::Center),
+        )
+        .push(
+            Text::new(status)
+                .width(Length::Fill)
+                .horizontal_alignment(HorizontalAlignment::Center),
+        )
+        .align_items(Align::Center)
+}
+
+/// The font of the digits and the size of the window they are scaled to.
+#[derive(Clone, Copy, Debug)]
+pub struct Digits {
+    pub font: Font,
+    width: u32,
+    height: u32,
+}
+
+impl Digits {
+    pub fn new(font: Font, (width, height): (u32, u32)) -> Self {
+        Self {
+            font,
+            width,
+            height,
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Returns the largest size of digits fitting `columns` clock faces side by side, leaving
+    /// `reserved` pixels of the height to the other widgets.
+    pub fn size(&self, columns: usize, reserved: u32) -> u16 {
+        // "00:00:00" takes about 5 times the size in width with most fonts.
+        let width = self.width as f32 / columns.max(1) as f32 / 5.0;
+        let height = self.height.saturating_sub(reserved) as f32 * 0.8;
+        width.min(height).clamp(20.0, f32::from(u16::MAX)) as u16
+    }
+}
+
+/// The fonts loaded so far by their paths, which are never freed.
+static FONTS: Mutex<Vec<(PathBuf, Font)>> = Mutex::new(Vec::new());
+
+/// Loads a TrueType or OpenType font file for the digits, or returns the one loaded from `path`
+/// before.
+///
+/// The font lives as long as the app since the renderer needs it forever, so each path is read
+/// only once. The renderer panics on a font it can't read, so the file is parsed and the glyphs
+/// of the clock drawn once here before it's handed over.
+pub fn load_font(path: &Path) -> anyhow::Result<Font> {
+    let mut fonts = FONTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&(_, font)) = fonts.iter().find(|(loaded, _)| loaded == path) {
+        return Ok(font);
+    }
+
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    check_font(&bytes).with_context(|| format!("failed to load {}", path.display()))?;
+    let font = Font::External {
+        // The renderer caches fonts by name, so each file needs its own.
+        name: Box::leak(path.display().to_string().into_boxed_str()),
+        bytes: Box::leak(bytes.into_boxed_slice()),
+    };
+    fonts.push((path.to_path_buf(), font));
+    Ok(font)
+}
+
+/// The characters drawn in the digits font.
+const DIGITS: &str = "0123456789:";
+
+/// Parses `bytes` the way the renderer does and draws the digits with them.
+fn check_font(bytes: &[u8]) -> anyhow::Result<()> {
+    // The parser indexes the file without checking its length, and the glyph tables are only read
+    // when a glyph is drawn, so a truncated or corrupt file panics either way.
+    let missing = panic::catch_unwind(AssertUnwindSafe(|| {
+        let font = rusttype::Font::from_bytes(bytes)?;
+        let scale = Scale::uniform(64.0);
+        font.v_metrics(scale);
+        Ok(DIGITS.chars().find(|&c| {
+            let glyph = font.glyph(c);
+            let missing = glyph.id().0 == 0;
+            glyph
+                .scaled(scale)
+                .positioned(point(0.0, 0.0))
+                .draw(|_, _, _| {});
+            missing
+        }))
+    }))
+    .map_err(|_| anyhow!("the font is corrupt"))?
+    .map_err(|e: rusttype::Error| anyhow!("not a TrueType or OpenType font: {}", e))?;
+    if let Some(c) = missing {
+        bail!("the font has no glyph for {:?}", c);
+    }
+    Ok(())
+}
+
+fn status(game: &Game, player: Player) -> String {
+    let moves = game.moves(player);
+    match game.overtime(player) {
+        Some(OvertimeState::ByoYomi { periods }) => {
+            format!("moves: {}, byo-yomi periods: {}", moves, periods)
+        }
+        Some(OvertimeState::Canadian { moves: left }) => {
+            format!("moves: {}, moves left in the block: {}", moves, left)
+        }
+        None => format!("moves: {}", moves),
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let sec = duration.as_secs();
+    let hour = sec / (60 * 60);
+    let sec = sec - hour * (60 * 60);
+    let min = sec / 60;
+    let sec = sec - min * 60;
+    format!("{:02}:{:02}:{:02}", hour, min, sec)
+}
+
+/// Emits the current instant every `interval`.
+pub fn ticks(interval: Duration) -> Subscription<Instant> {
+    Subscription::from_recipe(ClockRecipe(interval))
 }
 
 pub struct ClockRecipe(Duration);
@@ -137,14 +288,81 @@ impl<H: Hasher, E> Recipe<H, E> for ClockRecipe {
     }
 }
 
-pub struct ClockStyle;
+/// The colors of a clock face, derived from the state of the clock.
+pub struct ClockStyle {
+    text: Color,
+    background: Option<Color>,
+}
+
+impl ClockStyle {
+    pub fn new(
+        theme: &Theme,
+        display: &DisplaySettings,
+        game: &Game,
+        player: Player,
+        remaining: Duration,
+        now: Instant,
+    ) -> Self {
+        if let Some(flag_fall) = game
+            .history()
+            .flag_fall()
+            .filter(|flag_fall| flag_fall.player == player)
+        {
+            let phase = now.saturating_duration_since(flag_fall.at).as_millis()
+                / FLASH_INTERVAL.as_millis()
+                % 2;
+            return if display.flash && phase == 0 {
+                Self {
+                    text: color(theme.background),
+                    background: Some(color(theme.flag)),
+                }
+            } else {
+                Self {
+                    text: color(theme.flag),
+                    background: None,
+                }
+            };
+        }
+
+        let active = game.state() == GameState::Running(player);
+        let text = match display.threshold_color(remaining) {
+            Some(tint) => tint.resolve(theme),
+            None if active => theme.active,
+            None => theme.waiting,
+        };
+        Self {
+            text: color(text),
+            background: if active {
+                Some(color(theme.highlight))
+            } else {
+                None
+            },
+        }
+    }
+}
 
 impl button::StyleSheet for ClockStyle {
     fn active(&self) -> button::Style {
         button::Style {
-            background: None,
-            text_color: Color::BLACK,
+            background: self.background.map(Background::Color),
+            text_color: self.text,
             ..button::Style::default()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_what_the_renderer_cant_read() {
+        assert!(check_font(b"").is_err());
+        assert!(check_font(b"not a font at all").is_err());
+        // A TrueType signature followed by nothing but garbage.
+        let mut truncated = b"\x00\x01\x00\x00".to_vec();
+        truncated.extend_from_slice(&[0xff; 64]);
+        assert!(check_font(&truncated).is_err());
+        assert!(check_font(b"ttcf\x00\x01\x00\x00").is_err());
+    }
+}
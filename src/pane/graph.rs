@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use iced::{
+    button,
+    canvas::{self, layer::Cache, Canvas, Frame, Path, Stroke},
+    Align, Button, Color, Column, Container, Element, HorizontalAlignment, Length, Point, Row,
+    Size, Subscription, Text, VerticalAlignment,
+};
+
+use crate::{
+    game::{Game, Player},
+    settings::Theme,
+};
+
+use super::style;
+
+pub struct GraphPane {
+    usage: TimeUsage,
+    cache: Cache<TimeUsage>,
+    back_button: button::State,
+}
+
+impl GraphPane {
+    pub fn new(game: &Game, theme: &Theme) -> Self {
+        Self {
+            usage: TimeUsage::new(game, style::color(theme.text)),
+            cache: Cache::new(),
+            back_button: button::State::new(),
+        }
+    }
+
+    pub fn update(&mut self, _message: GraphMessage) {}
+
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, GraphMessage> {
+        let legend = self
+            .usage
+            .players
+            .iter()
+            .fold(Row::new().spacing(20), |row, usage| {
+                row.push(Text::new(format!("{:?}", usage.player)).color(color(usage.player)))
+            });
+
+        Container::new(
+            Column::new()
+                .push(
+                    Text::new("Remaining time and time per move")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .vertical_alignment(VerticalAlignment::Center),
+                )
+                .push(legend)
+                .push(
+                    Canvas::new()
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .push(self.cache.with(&self.usage)),
+                )
+                .push(
+                    Button::new(&mut self.back_button, Text::new("Back"))
+                        .style(style::button(theme))
+                        .on_press(GraphMessage::Back),
+                )
+                .align_items(Align::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(10)
+        .center_x()
+        .center_y()
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<GraphMessage> {
+        Subscription::none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum GraphMessage {
+    Back,
+}
+
+fn color(player: Player) -> Color {
+    match player {
+        Player::First => Color::from_rgb8(0x1f, 0x77, 0xb4),
+        Player::Second => Color::from_rgb8(0xff, 0x7f, 0x0e),
+    }
+}
+
+#[derive(Debug)]
+struct PlayerUsage {
+    player: Player,
+    /// The remaining time at the start of the game and after each move.
+    remaining: Vec<Duration>,
+    /// The time used for each move.
+    elapsed: Vec<Duration>,
+}
+
+/// The time usage of a game, drawn as the remaining time versus the move number on the upper
+/// half and as a bar per move on the lower half.
+#[derive(Debug)]
+struct TimeUsage {
+    players: Vec<PlayerUsage>,
+    axis: Color,
+}
+
+impl TimeUsage {
+    fn new(game: &Game, axis: Color) -> Self {
+        let players = game
+            .players()
+            .iter()
+            .map(|&player| {
+                let mut remaining = vec![game.time_limit()];
+                let mut elapsed = Vec::new();
+                for press in game.history().moves(player) {
+                    remaining.push(press.after.remaining[player.index()]);
+                    elapsed.push(press.elapsed);
+                }
+                PlayerUsage {
+                    player,
+                    remaining,
+                    elapsed,
+                }
+            })
+            .collect();
+        Self { players, axis }
+    }
+
+    fn moves(&self) -> usize {
+        self.players
+            .iter()
+            .map(|usage| usage.elapsed.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl canvas::Drawable for TimeUsage {
+    fn draw(&self, frame: &mut Frame) {
+        let moves = self.moves().max(1) as f32;
+        let width = frame.width();
+        let half = frame.height() / 2.0;
+        let axis = Stroke {
+            color: self.axis,
+            width: 1.0,
+            ..Stroke::default()
+        };
+
+        frame.stroke(
+            &Path::new(|path| {
+                path.move_to(Point::new(0.0, 0.0));
+                path.line_to(Point::new(0.0, half * 2.0));
+                path.move_to(Point::new(0.0, half));
+                path.line_to(Point::new(width, half));
+                path.move_to(Point::new(0.0, half * 2.0));
+                path.line_to(Point::new(width, half * 2.0));
+            }),
+            axis,
+        );
+
+        // Arbiters may add time, so the scale is the largest time ever left rather than the limit.
+        let max_remaining = self
+            .players
+            .iter()
+            .flat_map(|usage| usage.remaining.iter())
+            .max()
+            .copied()
+            .unwrap_or_else(|| Duration::new(0, 0))
+            .as_secs_f32()
+            .max(1.0);
+        for usage in &self.players {
+            let line = Path::new(|path| {
+                for (i, remaining) in usage.remaining.iter().enumerate() {
+                    let point = Point::new(
+                        width * i as f32 / moves,
+                        half * (1.0 - remaining.as_secs_f32() / max_remaining),
+                    );
+                    if i == 0 {
+                        path.move_to(point);
+                    } else {
+                        path.line_to(point);
+                    }
+                }
+            });
+            frame.stroke(
+                &line,
+                Stroke {
+                    color: color(usage.player),
+                    width: 2.0,
+                    ..Stroke::default()
+                },
+            );
+        }
+
+        let max_elapsed = self
+            .players
+            .iter()
+            .flat_map(|usage| usage.elapsed.iter())
+            .max()
+            .copied()
+            .unwrap_or_else(|| Duration::new(0, 0))
+            .as_secs_f32()
+            .max(1.0);
+        let slot = width / moves;
+        let bar = slot / self.players.len().max(1) as f32;
+        for (j, usage) in self.players.iter().enumerate() {
+            let bars = Path::new(|path| {
+                for (i, elapsed) in usage.elapsed.iter().enumerate() {
+                    let height = half * elapsed.as_secs_f32() / max_elapsed;
+                    path.rectangle(
+                        Point::new(slot * i as f32 + bar * j as f32, half * 2.0 - height),
+                        Size::new(bar * 0.9, height),
+                    );
+                }
+            });
+            frame.fill(&bars, color(usage.player));
+        }
+    }
+}
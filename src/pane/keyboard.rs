@@ -0,0 +1,176 @@
+use std::{
+    any::TypeId,
+    collections::HashSet,
+    hash::{Hash, Hasher},
+};
+
+use iced::Subscription;
+use iced_futures::{
+    futures::{future, StreamExt},
+    subscription::Recipe,
+    BoxStream,
+};
+use iced_native::{
+    input::{keyboard, ButtonState},
+    Event,
+};
+
+pub use keyboard::{KeyCode, ModifiersState};
+
+#[derive(Clone, Copy, Debug)]
+pub struct KeyPress {
+    pub key: KeyCode,
+    pub modifiers: ModifiersState,
+}
+
+impl KeyPress {
+    /// Returns whether this is the chord unlocking a locked pane, Ctrl+Shift+U.
+    pub fn is_unlock_chord(self) -> bool {
+        self.key == KeyCode::U && self.modifiers.control && self.modifiers.shift
+    }
+}
+
+/// The keys which can be bound to commands.
+const KEYS: &[KeyCode] = &[
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Escape,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Pause,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::LControl,
+    KeyCode::RControl,
+    KeyCode::LAlt,
+    KeyCode::RAlt,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Minus,
+    KeyCode::Equals,
+    KeyCode::NumpadEnter,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+];
+
+/// Returns the name of a key in the settings, which is the name of its variant.
+pub fn name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// Returns whether `name` is the name of a key which can be bound, ignoring the case.
+pub fn is_valid(name: &str) -> bool {
+    KEYS.iter()
+        .any(|&key| self::name(key).eq_ignore_ascii_case(name))
+}
+
+/// Emits the keys pressed, ignoring the repeats while a key is held down.
+pub fn presses() -> Subscription<KeyPress> {
+    Subscription::from_recipe(KeyPresses)
+}
+
+struct KeyPresses;
+
+impl<H: Hasher> Recipe<H, Event> for KeyPresses {
+    type Output = KeyPress;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, input: BoxStream<Event>) -> BoxStream<Self::Output> {
+        let mut held = HashSet::new();
+        input
+            .filter_map(move |event| {
+                let key = match event {
+                    Event::Keyboard(keyboard::Event::Input {
+                        state: ButtonState::Pressed,
+                        key_code,
+                        modifiers,
+                    }) if held.insert(key_code) => Some(KeyPress {
+                        key: key_code,
+                        modifiers,
+                    }),
+                    Event::Keyboard(keyboard::Event::Input {
+                        state: ButtonState::Released,
+                        key_code,
+                        ..
+                    }) => {
+                        held.remove(&key_code);
+                        None
+                    }
+                    _ => None,
+                };
+                future::ready(key)
+            })
+            .boxed()
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+use iced::{button, Align, Button, Column, Element, Length, Row, Space, Text};
+
+use crate::settings::Theme;
+
+use super::style;
+
+/// How long the confirmation of an unlock is offered for.
+const CONFIRM_INTERVAL: Duration = Duration::from_secs(3);
+
+/// A lock ignoring every input of a pane but a deliberate unlock gesture: pressing "Unlock" and
+/// then "Confirm" on the other side of the screen within a few seconds, or the key chord
+/// Ctrl+Shift+U.
+pub struct Lock {
+    state: LockState,
+    lock_button: button::State,
+    unlock_button: button::State,
+    confirm_button: button::State,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LockState {
+    Unlocked,
+    Locked,
+    /// "Unlock" has been pressed at the instant, waiting for the confirmation.
+    Confirming(Instant),
+}
+
+impl Lock {
+    pub fn new() -> Self {
+        Self {
+            state: LockState::Unlocked,
+            lock_button: button::State::new(),
+            unlock_button: button::State::new(),
+            confirm_button: button::State::new(),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.state != LockState::Unlocked
+    }
+
+    pub fn update(&mut self, message: LockMessage) {
+        let now = Instant::now();
+        self.state = match (self.state, message) {
+            (LockState::Unlocked, LockMessage::Lock) => LockState::Locked,
+            (LockState::Locked, LockMessage::Unlock)
+            | (LockState::Confirming(_), LockMessage::Unlock) => LockState::Confirming(now),
+            (LockState::Confirming(pressed), LockMessage::Confirm) => {
+                if now.saturating_duration_since(pressed) <= CONFIRM_INTERVAL {
+                    LockState::Unlocked
+                } else {
+                    LockState::Locked
+                }
+            }
+            (_, LockMessage::Chord) => LockState::Unlocked,
+            (state, _) => state,
+        };
+    }
+
+    /// Renders the button locking the pane.
+    pub fn lock_button(&mut self, theme: &Theme) -> Element<'_, LockMessage> {
+        Button::new(&mut self.lock_button, Text::new("Lock"))
+            .style(style::button(theme))
+            .on_press(LockMessage::Lock)
+            .into()
+    }
+
+    /// Renders the locked pane, which shows nothing but how to unlock it.
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, LockMessage> {
+        let unlock = Button::new(&mut self.unlock_button, Text::new("Unlock"))
+            .style(style::button(theme))
+            .on_press(LockMessage::Unlock);
+        let gesture = match self.state {
+            LockState::Confirming(_) => Row::new()
+                .push(unlock)
+                .push(Space::with_width(Length::Fill))
+                .push(
+                    Button::new(&mut self.confirm_button, Text::new("Confirm"))
+                        .style(style::button(theme))
+                        .on_press(LockMessage::Confirm),
+                ),
+            _ => Row::new().push(unlock),
+        };
+        Column::new()
+            .push(Text::new("LOCKED").size(60))
+            .push(Text::new(
+                "Press Unlock and then Confirm within 3 seconds, or Ctrl+Shift+U.",
+            ))
+            .push(gesture.width(Length::Fill).align_items(Align::Center))
+            .padding(20)
+            .spacing(20)
+            .align_items(Align::Center)
+            .into()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum LockMessage {
+    Lock,
+    Unlock,
+    Confirm,
+    /// The unlock key chord has been pressed.
+    Chord,
+}
@@ -1,118 +1,620 @@
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    path::Path,
+    time::{Duration, Instant},
+};
 
-use iced::{executor, Application, Column, Command, Element, Subscription};
-use log::debug;
+use iced::{
+    executor, window, Application, Column, Command, Container, Element, Font, Length, Subscription,
+};
+use log::{debug, info, warn};
 
-use crate::settings::Settings;
+use crate::{
+    game::{Game, GameState, OvertimeState, Player},
+    record::{self, Record},
+    session::{self, Session},
+    settings::{self, Action, Settings, Theme, WindowSettings},
+    sound::{Alarm, Audio, Pack, Sound, Timekeeper, Voice},
+};
 
+mod arbiter;
 mod clock;
+mod graph;
+mod keyboard;
+mod lock;
 mod pause;
+mod replay;
+mod resize;
+mod resume;
 mod setting;
+mod style;
+mod summary;
 
-use clock::{Clock1PMessage, Clock1PPane};
+use arbiter::{ArbiterMessage, ArbiterPane};
+use clock::{ClockMessage, ClockPane, Digits};
+use graph::{GraphMessage, GraphPane};
+use keyboard::KeyPress;
+use lock::LockMessage;
 use pause::{PauseMessage, PausePane};
+use replay::{ReplayMessage, ReplayPane};
+use resume::{ResumeMessage, ResumePane};
 use setting::{SettingMessage, SettingPane};
+use summary::{SummaryMessage, SummaryPane};
+
+/// How often a running game is saved between presses.
+const SESSION_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a fallen flag is shown before the summary.
+const FLAG_SHOWN: Duration = Duration::from_secs(3);
+
+#[derive(Default)]
+pub struct Flags {
+    pub settings: Settings,
+    /// The game interrupted last time, if any.
+    pub session: Option<Session>,
+    /// The window settings overridden by the command line.
+    pub window: WindowSettings,
+}
 
 pub struct RootPane {
     children: VecDeque<Pane>,
     settings: Settings,
+    game: Game,
+    session_saved: Instant,
+    audio: Audio,
+    alarm: Alarm,
+    timekeeper: Timekeeper,
+    /// When the summary is to be shown after a flag fall.
+    summary_due: Option<Instant>,
+    /// The theme selected in the settings.
+    theme: Theme,
+    digits: Digits,
+    /// Whether the window is fullscreen.
+    mode: window::Mode,
 }
 
 impl Application for RootPane {
     type Executor = executor::Default;
     type Message = RootMessage;
-    type Flags = ();
+    type Flags = Flags;
 
-    fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
         let mut children = VecDeque::new();
-        let settings = Settings::default();
-        children.push_back(Pane::Setting(SettingPane::new()));
-        (Self { children, settings }, Command::none())
+        let settings = flags.settings;
+        let game = Game::new(&settings);
+        let theme = settings.theme();
+        let window = flags.window;
+        let digits = Digits::new(digits_font(&settings), (window.width, window.height));
+        let mode = if window.fullscreen || window.kiosk {
+            window::Mode::Fullscreen
+        } else {
+            window::Mode::Windowed
+        };
+        let audio = Audio::new(
+            Pack::load_or_default(settings.sound.pack.as_deref()),
+            Voice::load_or_default(settings.sound.voice.samples.as_deref()),
+        );
+        children.push_back(Pane::Setting(SettingPane::new(&settings)));
+        if let Some(session) = flags.session {
+            children.push_back(Pane::Resume(ResumePane::new(session)));
+        }
+        (
+            Self {
+                children,
+                settings,
+                game,
+                session_saved: Instant::now(),
+                audio,
+                alarm: Alarm::new(),
+                timekeeper: Timekeeper::new(),
+                summary_due: None,
+                theme,
+                digits,
+                mode,
+            },
+            Command::none(),
+        )
     }
 
     fn title(&self) -> String {
         "GameClocKBone".to_string()
     }
 
+    fn mode(&self) -> window::Mode {
+        self.mode
+    }
+
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
-        debug!("Update RootPane (message: {:?})", message);
+        debug!(
+            "Update RootPane (message: {:?}, state: {:?})",
+            message,
+            self.game.state()
+        );
+
+        if let Self::Message::Resized(width, height) = message {
+            self.digits.resize(width, height);
+            return Command::none();
+        }
+
+        if let Self::Message::Key(key) = message {
+            return match self.key_message(key) {
+                Some(message) => self.update(message),
+                None => Command::none(),
+            };
+        }
+
+        if self.is_locked() && !message.unlocks() {
+            debug!("Ignore {:?} while locked.", message);
+            return Command::none();
+        }
 
         if let Some(pane) = self.children.back_mut() {
             pane.update(message.clone());
         }
 
+        let now = Instant::now();
+        let state = self.game.state();
+        let save_session = match &message {
+            Self::Message::Clock(ClockMessage::Tick(_)) => {
+                now.saturating_duration_since(self.session_saved) >= SESSION_INTERVAL
+            }
+            // Leaving for the settings abandons the game, whose session is discarded below.
+            Self::Message::Pause(PauseMessage::Settings) => false,
+            Self::Message::Clock(_)
+            | Self::Message::Pause(_)
+            | Self::Message::Arbiter(ArbiterMessage::Adjust(_)) => true,
+            _ => false,
+        };
         match message {
-            Self::Message::Clock1P(message) => match message {
-                Clock1PMessage::Pause => {
+            Self::Message::Clock(message) => match message {
+                ClockMessage::Press(player) if state.accepts_press() => {
+                    self.game.press(player, now);
+                    // A press can be the first to notice a flag fall.
+                    if self.game.state().is_finished() {
+                        self.summary_due = Some(now + FLAG_SHOWN);
+                    }
+                }
+                ClockMessage::Pause => {
+                    // The menu is reachable in every state, but only a running game gets paused.
+                    // A running game isn't paused only if its flag has fallen in the meantime.
+                    if state.accepts_pause() && !self.game.pause(now) {
+                        self.summary_due = Some(now + FLAG_SHOWN);
+                    }
                     self.children.push_back(Pane::Pause(PausePane::new()));
                 }
-                _ => {}
+                ClockMessage::Tick(now) => {
+                    self.game.tick(now);
+                    self.play_sounds(now);
+                    if !state.is_finished() && self.game.state().is_finished() {
+                        self.summary_due = Some(now + FLAG_SHOWN);
+                    }
+                    if matches!(self.summary_due, Some(due) if now >= due) {
+                        self.summary_due = None;
+                        self.children.push_back(Pane::Summary(SummaryPane::new()));
+                    }
+                }
+                message => {
+                    debug!("Ignore {:?} in {:?}", message, state);
+                }
+            },
+            Self::Message::Graph(message) => match message {
+                GraphMessage::Back => {
+                    self.children.pop_back();
+                }
             },
             Self::Message::Pause(message) => match message {
                 PauseMessage::Settings => {
+                    if let Err(e) = session::discard() {
+                        warn!("Failed to discard the session: {:#}", e);
+                    }
                     self.children.clear();
-                    self.children.push_back(Pane::Setting(SettingPane::new()));
+                    self.children
+                        .push_back(Pane::Setting(SettingPane::new(&self.settings)));
                 }
                 PauseMessage::Back => {
+                    if state.accepts_resume() {
+                        self.game.resume(now);
+                    }
                     self.children.pop_back();
                 }
                 PauseMessage::Reset => {
+                    self.start_game();
+                }
+                PauseMessage::Undo => {
+                    self.game.undo(now);
+                }
+                PauseMessage::Redo => {
+                    self.game.redo(now);
+                }
+                PauseMessage::Graph => {
+                    self.children
+                        .push_back(Pane::Graph(GraphPane::new(&self.game, &self.theme)));
+                }
+                PauseMessage::Finish if state.accepts_finish() => {
+                    self.game.finish(now);
+                    self.children.pop_back();
+                    self.children.push_back(Pane::Summary(SummaryPane::new()));
+                }
+                PauseMessage::Export => {
+                    let status = self.export();
+                    if let Some(Pane::Pause(pane)) = self.children.back_mut() {
+                        pane.set_status(status);
+                    }
+                }
+                PauseMessage::Arbiter => match &self.settings.arbiter_pin {
+                    Some(pin) => {
+                        self.children
+                            .push_back(Pane::Arbiter(ArbiterPane::new(pin.clone())));
+                    }
+                    None => {
+                        if let Some(Pane::Pause(pane)) = self.children.back_mut() {
+                            pane.set_status(
+                                "Set an arbiter PIN in the settings first.".to_string(),
+                            );
+                        }
+                    }
+                },
+                _ => {}
+            },
+            Self::Message::Arbiter(message) => match message {
+                ArbiterMessage::Adjust(adjustment) => {
+                    self.game.adjust(adjustment, now);
+                }
+                ArbiterMessage::Back => {
+                    self.children.pop_back();
+                }
+                _ => {}
+            },
+            Self::Message::Summary(message) => match message {
+                SummaryMessage::Back => {
+                    self.children.pop_back();
+                }
+                SummaryMessage::Graph => {
+                    self.children
+                        .push_back(Pane::Graph(GraphPane::new(&self.game, &self.theme)));
+                }
+                SummaryMessage::Settings => {
                     self.children.clear();
-                    self.children.push_back(Pane::Clock1P(Clock1PPane::new(
-                        self.settings.clock.time_limit,
-                    )));
+                    self.children
+                        .push_back(Pane::Setting(SettingPane::new(&self.settings)));
+                }
+                SummaryMessage::Reset => {
+                    self.start_game();
+                }
+                SummaryMessage::Export => {
+                    let status = self.export();
+                    if let Some(Pane::Summary(pane)) = self.children.back_mut() {
+                        pane.set_status(status);
+                    }
+                }
+                SummaryMessage::Replay => {
+                    self.children
+                        .push_back(Pane::Replay(ReplayPane::new(Record::new(&self.game))));
+                }
+                SummaryMessage::MergePgn(path) => {
+                    let status = self.merge_pgn(Path::new(&path));
+                    if let Some(Pane::Summary(pane)) = self.children.back_mut() {
+                        pane.set_status(status);
+                    }
+                }
+                _ => {}
+            },
+            Self::Message::Replay(message) => {
+                if let ReplayMessage::Back = message {
+                    self.children.pop_back();
+                }
+            }
+            Self::Message::Resume(message) => match message {
+                ResumeMessage::Resume => {
+                    if let Some(Pane::Resume(pane)) = self.children.pop_back() {
+                        let session = pane.into_session();
+                        info!("Resume the interrupted game.");
+                        let arbiter_pin = self.settings.arbiter_pin.take();
+                        self.settings = Settings {
+                            arbiter_pin,
+                            ..session.settings
+                        };
+                        self.theme = self.settings.theme();
+                        self.digits.font = digits_font(&self.settings);
+                        self.game = session.game;
+                        self.alarm = Alarm::new();
+                        self.timekeeper = Timekeeper::new();
+                        self.audio
+                            .set_pack(Pack::load_or_default(self.settings.sound.pack.as_deref()));
+                        self.audio.set_voice(Voice::load_or_default(
+                            self.settings.sound.voice.samples.as_deref(),
+                        ));
+                        self.children.clear();
+                        self.summary_due = None;
+                        self.children
+                            .push_back(Pane::Clock(ClockPane::new(&self.settings.display)));
+                        self.children.push_back(Pane::Pause(PausePane::new()));
+                    }
+                }
+                ResumeMessage::Discard => {
+                    if let Err(e) = session::discard() {
+                        warn!("Failed to discard the session: {:#}", e);
+                    }
+                    self.children.pop_back();
                 }
             },
             Self::Message::Setting(message) => match message {
                 SettingMessage::Done(settings) => {
+                    if let Err(e) = settings::save(&settings) {
+                        warn!("Failed to save the settings: {:#}", e);
+                    }
+                    if settings.sound.pack != self.settings.sound.pack {
+                        self.audio
+                            .set_pack(Pack::load_or_default(settings.sound.pack.as_deref()));
+                    }
+                    if settings.sound.voice.samples != self.settings.sound.voice.samples {
+                        self.audio.set_voice(Voice::load_or_default(
+                            settings.sound.voice.samples.as_deref(),
+                        ));
+                    }
+                    if settings.display.font != self.settings.display.font {
+                        self.digits.font = digits_font(&settings);
+                    }
                     self.settings = settings;
-                    self.children.clear();
-                    self.children.push_back(Pane::Clock1P(Clock1PPane::new(
-                        self.settings.clock.time_limit,
-                    )));
+                    self.theme = self.settings.theme();
+                    self.start_game();
                 }
+                SettingMessage::Replay(path) => match Record::load(Path::new(&path)) {
+                    Ok(record) => {
+                        self.children
+                            .push_back(Pane::Replay(ReplayPane::new(record)));
+                    }
+                    Err(e) => {
+                        warn!("Failed to load {}: {:#}", path, e);
+                        if let Some(Pane::Setting(pane)) = self.children.back_mut() {
+                            pane.set_replay_error(format!("Failed to load: {:#}", e));
+                        }
+                    }
+                },
                 _ => {}
             },
+            Self::Message::Key(_) | Self::Message::Resized(..) => {}
         };
 
+        if save_session {
+            self.save_session(now);
+        }
+
         Command::none()
     }
 
-    fn view(&mut self) -> Element<Self::Message> {
-        if let Some(pane) = self.children.back_mut() {
-            pane.view()
+    fn view(&mut self) -> Element<'_, Self::Message> {
+        let game = &self.game;
+        let theme = &self.theme;
+        let digits = &self.digits;
+        let content = if let Some(pane) = self.children.back_mut() {
+            pane.view(game, theme, digits)
         } else {
             Column::new().into()
-        }
+        };
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(style::root(theme))
+            .into()
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
         if let Some(pane) = self.children.back() {
-            pane.subscription()
+            // Keys are listened to only where they can't be typed into a text input.
+            let keys = match pane {
+                Pane::Clock(_) | Pane::Pause(_) | Pane::Summary(_) => {
+                    keyboard::presses().map(RootMessage::Key)
+                }
+                _ => Subscription::none(),
+            };
+            Subscription::batch(vec![
+                pane.subscription(),
+                keys,
+                resize::resizes().map(|(width, height)| RootMessage::Resized(width, height)),
+            ])
         } else {
-            Subscription::none()
+            resize::resizes().map(|(width, height)| RootMessage::Resized(width, height))
         }
     }
 }
 
+impl RootPane {
+    fn start_game(&mut self) {
+        self.game = Game::new(&self.settings);
+        self.alarm = Alarm::new();
+        self.timekeeper = Timekeeper::new();
+        self.summary_due = None;
+        self.save_session(Instant::now());
+        self.children.clear();
+        self.children
+            .push_back(Pane::Clock(ClockPane::new(&self.settings.display)));
+    }
+
+    fn play_sounds(&mut self, now: Instant) {
+        let sound = &self.settings.sound;
+        let events = self.alarm.update(&self.game, now, sound);
+        let number = self.timekeeper.update(&self.game, now, &sound.voice);
+        if !sound.enabled {
+            return;
+        }
+
+        // The timekeeper replaces the warnings and the countdown during byo-yomi.
+        let reading = sound.voice.enabled
+            && match self.game.state().player_to_move() {
+                Some(player) => matches!(
+                    self.game.overtime(player),
+                    Some(OvertimeState::ByoYomi { .. })
+                ),
+                None => false,
+            };
+        for event in events {
+            if !(reading && (event == Sound::Warning || event == Sound::Tick)) {
+                self.audio.play(event, sound.volume);
+            }
+        }
+        if let Some(number) = number.filter(|_| reading) {
+            self.audio.speak(number, sound.volume);
+        }
+    }
+
+    /// Returns whether the pane in front ignores every input but the unlock gesture.
+    fn is_locked(&self) -> bool {
+        match self.children.back() {
+            Some(Pane::Pause(pane)) => pane.is_locked(),
+            Some(Pane::Summary(pane)) => pane.is_locked(),
+            _ => false,
+        }
+    }
+
+    /// Translates a key press into the message of the pane in front bound to it, if any.
+    fn key_message(&self, key: KeyPress) -> Option<RootMessage> {
+        if key.is_unlock_chord() {
+            return match self.children.back()? {
+                Pane::Pause(_) => Some(RootMessage::Pause(PauseMessage::Lock(LockMessage::Chord))),
+                Pane::Summary(_) => Some(RootMessage::Summary(SummaryMessage::Lock(
+                    LockMessage::Chord,
+                ))),
+                _ => None,
+            };
+        }
+        let action = self.settings.bindings.action(&keyboard::name(key.key))?;
+        let players = self.game.players();
+        let message = match (self.children.back()?, action) {
+            (Pane::Clock(_), Action::PressFirst) => ClockMessage::Press(Player::First),
+            (Pane::Clock(_), Action::PressSecond) if players.contains(&Player::Second) => {
+                ClockMessage::Press(Player::Second)
+            }
+            (Pane::Clock(_), Action::PressActive) => {
+                // The last player starts the clock of the first one.
+                let player = self
+                    .game
+                    .state()
+                    .player_to_move()
+                    .or_else(|| players.last().copied())?;
+                ClockMessage::Press(player)
+            }
+            (Pane::Clock(_), Action::Pause) => ClockMessage::Pause,
+            (Pane::Pause(_), Action::Pause) => return Some(RootMessage::Pause(PauseMessage::Back)),
+            _ => return None,
+        };
+        Some(RootMessage::Clock(message))
+    }
+
+    /// Exports the record of the game and returns a message telling where to.
+    fn export(&self) -> String {
+        let dir = match record::export_dir(&self.settings) {
+            Some(dir) => dir,
+            None => return "No directory to export to.".to_string(),
+        };
+        match record::export(&self.game, &dir) {
+            Ok(_) => format!("Exported to {}.", dir.display()),
+            Err(e) => {
+                warn!("Failed to export the record: {:#}", e);
+                format!("Failed to export: {:#}", e)
+            }
+        }
+    }
+
+    /// Adds the clock annotations to the PGN file `source` and returns a message telling where
+    /// the result is.
+    fn merge_pgn(&self, source: &Path) -> String {
+        let dir = match record::export_dir(&self.settings) {
+            Some(dir) => dir,
+            None => return "No directory to export to.".to_string(),
+        };
+        match record::merge_pgn(&self.game, source, &dir) {
+            Ok(path) => format!("Written to {}.", path.display()),
+            Err(e) => {
+                warn!("Failed to add the clock to {}: {:#}", source.display(), e);
+                format!("Failed to add the clock: {:#}", e)
+            }
+        }
+    }
+
+    /// Saves the game in progress so that it can be resumed after a crash, or discards the saved
+    /// one if there is nothing to resume any more.
+    fn save_session(&mut self, now: Instant) {
+        self.session_saved = now;
+        let result = match self.game.state() {
+            GameState::Running(_) | GameState::Paused(_) => session::save(&Session::new(
+                self.settings.clone(),
+                self.game.suspended(now),
+            )),
+            GameState::Ready | GameState::Finished(_) => session::discard(),
+        };
+        if let Err(e) = result {
+            warn!("Failed to save the session: {:#}", e);
+        }
+    }
+}
+
+/// Loads the font of the digits in the settings, falling back on the default one.
+fn digits_font(settings: &Settings) -> Font {
+    match &settings.display.font {
+        Some(path) => clock::load_font(path).unwrap_or_else(|e| {
+            warn!("Failed to load the font of the digits: {:#}", e);
+            Font::Default
+        }),
+        None => Font::Default,
+    }
+}
+
+// A message lives only until it's handled, so the settings aren't worth boxing.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug)]
 pub enum RootMessage {
-    Clock1P(Clock1PMessage),
+    Arbiter(ArbiterMessage),
+    Clock(ClockMessage),
+    Graph(GraphMessage),
+    Key(KeyPress),
     Pause(PauseMessage),
+    Replay(ReplayMessage),
+    /// The window has been resized to the width and the height.
+    Resized(u32, u32),
+    Resume(ResumeMessage),
     Setting(SettingMessage),
+    Summary(SummaryMessage),
 }
 
+impl RootMessage {
+    /// Returns whether this message is part of the gesture unlocking a locked pane.
+    fn unlocks(&self) -> bool {
+        matches!(
+            self,
+            RootMessage::Pause(PauseMessage::Lock(_))
+                | RootMessage::Summary(SummaryMessage::Lock(_))
+        )
+    }
+}
+
+// There are only a few panes at a time, so the large ones aren't worth boxing.
+#[allow(clippy::large_enum_variant)]
 enum Pane {
-    Clock1P(Clock1PPane),
+    Arbiter(ArbiterPane),
+    Clock(ClockPane),
+    Graph(GraphPane),
     Pause(PausePane),
+    Replay(ReplayPane),
+    Resume(ResumePane),
     Setting(SettingPane),
+    Summary(SummaryPane),
 }
 
 impl Pane {
     fn update(&mut self, message: RootMessage) {
         match self {
-            Pane::Clock1P(pane) => {
-                if let RootMessage::Clock1P(message) = message {
+            Pane::Arbiter(pane) => {
+                if let RootMessage::Arbiter(message) = message {
+                    pane.update(message);
+                }
+            }
+            Pane::Clock(pane) => {
+                if let RootMessage::Clock(message) = message {
+                    pane.update(message);
+                }
+            }
+            Pane::Graph(pane) => {
+                if let RootMessage::Graph(message) = message {
                     pane.update(message);
                 }
             }
@@ -121,27 +623,52 @@ impl Pane {
                     pane.update(message);
                 }
             }
+            Pane::Replay(pane) => {
+                if let RootMessage::Replay(message) = message {
+                    pane.update(message);
+                }
+            }
+            Pane::Resume(pane) => {
+                if let RootMessage::Resume(message) = message {
+                    pane.update(message);
+                }
+            }
             Pane::Setting(pane) => {
                 if let RootMessage::Setting(message) = message {
                     pane.update(message);
                 }
             }
+            Pane::Summary(pane) => {
+                if let RootMessage::Summary(message) = message {
+                    pane.update(message);
+                }
+            }
         }
     }
 
-    fn view(&mut self) -> Element<RootMessage> {
+    fn view(&mut self, game: &Game, theme: &Theme, digits: &Digits) -> Element<'_, RootMessage> {
         match self {
-            Pane::Clock1P(pane) => pane.view().map(RootMessage::Clock1P),
-            Pane::Pause(pane) => pane.view().map(RootMessage::Pause),
-            Pane::Setting(pane) => pane.view().map(RootMessage::Setting),
+            Pane::Arbiter(pane) => pane.view(game, theme).map(RootMessage::Arbiter),
+            Pane::Clock(pane) => pane.view(game, theme, digits).map(RootMessage::Clock),
+            Pane::Graph(pane) => pane.view(theme).map(RootMessage::Graph),
+            Pane::Pause(pane) => pane.view(game, theme).map(RootMessage::Pause),
+            Pane::Replay(pane) => pane.view(theme, digits).map(RootMessage::Replay),
+            Pane::Resume(pane) => pane.view(theme).map(RootMessage::Resume),
+            Pane::Setting(pane) => pane.view(theme).map(RootMessage::Setting),
+            Pane::Summary(pane) => pane.view(game, theme).map(RootMessage::Summary),
         }
     }
 
     fn subscription(&self) -> Subscription<RootMessage> {
         match self {
-            Pane::Clock1P(pane) => pane.subscription().map(RootMessage::Clock1P),
+            Pane::Arbiter(pane) => pane.subscription().map(RootMessage::Arbiter),
+            Pane::Clock(pane) => pane.subscription().map(RootMessage::Clock),
+            Pane::Graph(pane) => pane.subscription().map(RootMessage::Graph),
             Pane::Pause(pane) => pane.subscription().map(RootMessage::Pause),
+            Pane::Replay(pane) => pane.subscription().map(RootMessage::Replay),
+            Pane::Resume(pane) => pane.subscription().map(RootMessage::Resume),
             Pane::Setting(pane) => pane.subscription().map(RootMessage::Setting),
+            Pane::Summary(pane) => pane.subscription().map(RootMessage::Summary),
         }
     }
 }
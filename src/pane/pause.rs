@@ -1,9 +1,25 @@
-use iced::{button, Button, Container, Element, Length, Row, Subscription, Text};
+use iced::{button, Align, Button, Column, Container, Element, Length, Row, Subscription, Text};
+
+use crate::{game::Game, settings::Theme};
+
+use super::{
+    lock::{Lock, LockMessage},
+    style,
+};
 
 pub struct PausePane {
     settings_button: button::State,
     back_button: button::State,
     reset_button: button::State,
+    undo_button: button::State,
+    redo_button: button::State,
+    arbiter_button: button::State,
+    finish_button: button::State,
+    graph_button: button::State,
+    export_button: button::State,
+    /// The result of the last export.
+    status: String,
+    lock: Lock,
 }
 
 impl PausePane {
@@ -12,26 +28,99 @@ impl PausePane {
             settings_button: button::State::new(),
             back_button: button::State::new(),
             reset_button: button::State::new(),
+            undo_button: button::State::new(),
+            redo_button: button::State::new(),
+            arbiter_button: button::State::new(),
+            finish_button: button::State::new(),
+            graph_button: button::State::new(),
+            export_button: button::State::new(),
+            status: String::new(),
+            lock: Lock::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: PauseMessage) {
+        if let PauseMessage::Lock(message) = message {
+            self.lock.update(message);
         }
     }
 
-    pub fn update(&mut self, _message: PauseMessage) {}
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+
+    pub fn view(&mut self, game: &Game, theme: &Theme) -> Element<'_, PauseMessage> {
+        if self.lock.is_locked() {
+            return Container::new(self.lock.view(theme).map(PauseMessage::Lock))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        let mut undo_button =
+            Button::new(&mut self.undo_button, Text::new("Undo")).style(style::button(theme));
+        if game.state().accepts_undo() && game.history().can_undo() {
+            undo_button = undo_button.on_press(PauseMessage::Undo);
+        }
+        let mut redo_button =
+            Button::new(&mut self.redo_button, Text::new("Redo")).style(style::button(theme));
+        if game.state().accepts_redo() && game.history().can_redo() {
+            redo_button = redo_button.on_press(PauseMessage::Redo);
+        }
+
+        let mut finish_button =
+            Button::new(&mut self.finish_button, Text::new("Finish")).style(style::button(theme));
+        if game.state().accepts_finish() {
+            finish_button = finish_button.on_press(PauseMessage::Finish);
+        }
 
-    pub fn view(&mut self) -> Element<PauseMessage> {
         Container::new(
-            Row::new()
-                .push(
-                    Button::new(&mut self.settings_button, Text::new("Settings"))
-                        .on_press(PauseMessage::Settings),
-                )
+            Column::new()
                 .push(
-                    Button::new(&mut self.back_button, Text::new("Back"))
-                        .on_press(PauseMessage::Back),
+                    Row::new()
+                        .push(
+                            Button::new(&mut self.settings_button, Text::new("Settings"))
+                                .style(style::button(theme))
+                                .on_press(PauseMessage::Settings),
+                        )
+                        .push(
+                            Button::new(&mut self.back_button, Text::new("Back"))
+                                .style(style::button(theme))
+                                .on_press(PauseMessage::Back),
+                        )
+                        .push(
+                            Button::new(&mut self.reset_button, Text::new("Reset"))
+                                .style(style::button(theme))
+                                .on_press(PauseMessage::Reset),
+                        )
+                        .push(undo_button)
+                        .push(redo_button)
+                        .push(
+                            Button::new(&mut self.arbiter_button, Text::new("Arbiter"))
+                                .style(style::button(theme))
+                                .on_press(PauseMessage::Arbiter),
+                        )
+                        .push(finish_button)
+                        .push(
+                            Button::new(&mut self.graph_button, Text::new("Graph"))
+                                .style(style::button(theme))
+                                .on_press(PauseMessage::Graph),
+                        )
+                        .push(
+                            Button::new(&mut self.export_button, Text::new("Export"))
+                                .style(style::button(theme))
+                                .on_press(PauseMessage::Export),
+                        )
+                        .push(self.lock.lock_button(theme).map(PauseMessage::Lock)),
                 )
-                .push(
-                    Button::new(&mut self.reset_button, Text::new("Reset"))
-                        .on_press(PauseMessage::Reset),
-                ),
+                .push(Text::new(&self.status))
+                .align_items(Align::Center),
         )
         .width(Length::Fill)
         .height(Length::Fill)
@@ -50,4 +139,11 @@ pub enum PauseMessage {
     Settings,
     Back,
     Reset,
+    Undo,
+    Redo,
+    Arbiter,
+    Finish,
+    Graph,
+    Export,
+    Lock(LockMessage),
 }
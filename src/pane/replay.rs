@@ -0,0 +1,186 @@
+use std::time::{Duration, Instant};
+
+use iced::{
+    button, slider, Align, Button, Column, Container, Element, Length, Radio, Row, Slider,
+    Subscription, Text,
+};
+
+use crate::{game::Player, record::Record, settings::Theme};
+
+use super::{
+    clock::{face, format_duration, ticks, Digits},
+    style,
+};
+
+/// The speeds the replay can be played at.
+const SPEEDS: [u32; 5] = [1, 2, 5, 10, 30];
+/// The height taken by the widgets below the digits.
+const REPLAY_RESERVED: u32 = 200;
+
+/// Plays back the clocks of a recorded game.
+pub struct ReplayPane {
+    record: Record,
+    /// The time from the start of the game shown in seconds.
+    offset: f64,
+    playing: bool,
+    speed: u32,
+    /// The instant the offset has been advanced at last while playing.
+    last_tick: Option<Instant>,
+    scrubber: slider::State,
+    play_button: button::State,
+    back_button: button::State,
+}
+
+impl ReplayPane {
+    pub fn new(record: Record) -> Self {
+        Self {
+            record,
+            offset: 0.0,
+            playing: false,
+            speed: 1,
+            last_tick: None,
+            scrubber: slider::State::new(),
+            play_button: button::State::new(),
+            back_button: button::State::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: ReplayMessage) {
+        match message {
+            ReplayMessage::Tick(now) => {
+                if let Some(last_tick) = self.last_tick {
+                    let elapsed = now.saturating_duration_since(last_tick).as_secs_f64();
+                    self.offset += elapsed * f64::from(self.speed);
+                }
+                self.last_tick = Some(now);
+                if self.offset >= self.record.length() {
+                    self.offset = self.record.length();
+                    self.playing = false;
+                }
+            }
+            ReplayMessage::Seek(offset) => {
+                self.offset = f64::from(offset);
+            }
+            ReplayMessage::PlayPause => {
+                self.playing = !self.playing;
+                self.last_tick = None;
+                if self.playing && self.offset >= self.record.length() {
+                    self.offset = 0.0;
+                }
+            }
+            ReplayMessage::SpeedSelected(speed) => {
+                self.speed = speed;
+            }
+            ReplayMessage::Back => {}
+        }
+    }
+
+    pub fn view(&mut self, theme: &Theme, digits: &Digits) -> Element<'_, ReplayMessage> {
+        let position = self.record.position(self.offset);
+        let all: &'static [Player] = &Player::ALL;
+        let size = digits.size(self.record.players, REPLAY_RESERVED);
+        let clocks =
+            all[..self.record.players.min(all.len())]
+                .iter()
+                .fold(Row::new(), |row, &player| {
+                    let index = player.index();
+                    let remaining = position.remaining.get(index).copied().unwrap_or(0.0);
+                    let moves = position.moves.get(index).copied().unwrap_or(0);
+                    let status = if position.running == Some(player) {
+                        format!("moves: {} (to move)", moves)
+                    } else {
+                        format!("moves: {}", moves)
+                    };
+                    row.push(
+                        Container::new(face(
+                            Duration::from_secs_f64(remaining.max(0.0)),
+                            status,
+                            size,
+                            digits.font,
+                        ))
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .center_y(),
+                    )
+                });
+
+        let length = self.record.length();
+        let scrubber = Row::new()
+            .push(Text::new(format_duration(Duration::from_secs_f64(
+                self.offset,
+            ))))
+            .push(Slider::new(
+                &mut self.scrubber,
+                0.0..=length as f32,
+                self.offset as f32,
+                ReplayMessage::Seek,
+            ))
+            .push(Text::new(format_duration(Duration::from_secs_f64(length))))
+            .spacing(10)
+            .align_items(Align::Center);
+
+        let speed = self.speed;
+        let controls = SPEEDS.iter().fold(
+            Row::new().push(
+                Button::new(
+                    &mut self.play_button,
+                    Text::new(if self.playing { "Pause" } else { "Play" }),
+                )
+                .style(style::button(theme))
+                .on_press(ReplayMessage::PlayPause),
+            ),
+            |row, &value| {
+                row.push(Radio::new(
+                    value,
+                    format!("x{}", value),
+                    Some(speed),
+                    ReplayMessage::SpeedSelected,
+                ))
+            },
+        );
+
+        Container::new(
+            Column::new()
+                .push(clocks.height(Length::Fill))
+                .push(Text::new(format!(
+                    "TimeControl: {}",
+                    self.record.time_control
+                )))
+                .push(scrubber)
+                .push(
+                    controls
+                        .push(
+                            Button::new(&mut self.back_button, Text::new("Back"))
+                                .style(style::button(theme))
+                                .on_press(ReplayMessage::Back),
+                        )
+                        .spacing(10)
+                        .align_items(Align::Center),
+                )
+                .padding(10)
+                .align_items(Align::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<ReplayMessage> {
+        if self.playing {
+            ticks(Duration::from_millis(50)).map(ReplayMessage::Tick)
+        } else {
+            Subscription::none()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ReplayMessage {
+    Tick(Instant),
+    Seek(f32),
+    PlayPause,
+    SpeedSelected(u32),
+    Back,
+}
@@ -0,0 +1,41 @@
+use std::{
+    any::TypeId,
+    hash::{Hash, Hasher},
+};
+
+use iced::Subscription;
+use iced_futures::{
+    futures::{future, StreamExt},
+    subscription::Recipe,
+    BoxStream,
+};
+use iced_native::{window, Event};
+
+/// Emits the width and the height of the window whenever it is resized.
+pub fn resizes() -> Subscription<(u32, u32)> {
+    Subscription::from_recipe(Resizes)
+}
+
+struct Resizes;
+
+impl<H: Hasher> Recipe<H, Event> for Resizes {
+    type Output = (u32, u32);
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, input: BoxStream<Event>) -> BoxStream<Self::Output> {
+        input
+            .filter_map(|event| {
+                let size = match event {
+                    Event::Window(window::Event::Resized { width, height }) => {
+                        Some((width, height))
+                    }
+                    _ => None,
+                };
+                future::ready(size)
+            })
+            .boxed()
+    }
+}
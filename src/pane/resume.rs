@@ -0,0 +1,86 @@
+use std::time::Instant;
+
+use iced::{button, Align, Button, Column, Container, Element, Length, Row, Subscription, Text};
+
+use crate::{session::Session, settings::Theme};
+
+use super::{clock::format_duration, style};
+
+/// Offers to resume the game interrupted last time.
+pub struct ResumePane {
+    session: Session,
+    resume_button: button::State,
+    discard_button: button::State,
+}
+
+impl ResumePane {
+    pub fn new(session: Session) -> Self {
+        Self {
+            session,
+            resume_button: button::State::new(),
+            discard_button: button::State::new(),
+        }
+    }
+
+    pub fn into_session(self) -> Session {
+        self.session
+    }
+
+    pub fn update(&mut self, _message: ResumeMessage) {}
+
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, ResumeMessage> {
+        let game = &self.session.game;
+        let now = Instant::now();
+        let clocks = game
+            .players()
+            .iter()
+            .fold(Column::new(), |column, &player| {
+                column.push(Text::new(format!(
+                    "{:?}: {} left, {} moves",
+                    player,
+                    format_duration(game.remaining(player, now)),
+                    game.moves(player)
+                )))
+            });
+        let to_move = match game.state().player_to_move() {
+            Some(player) => format!("{:?} to move", player),
+            None => "not started".to_string(),
+        };
+
+        Container::new(
+            Column::new()
+                .push(Text::new("The last game has been interrupted."))
+                .push(clocks)
+                .push(Text::new(to_move))
+                .push(
+                    Row::new()
+                        .push(
+                            Button::new(&mut self.resume_button, Text::new("Resume"))
+                                .style(style::button(theme))
+                                .on_press(ResumeMessage::Resume),
+                        )
+                        .push(
+                            Button::new(&mut self.discard_button, Text::new("Discard"))
+                                .style(style::button(theme))
+                                .on_press(ResumeMessage::Discard),
+                        ),
+                )
+                .align_items(Align::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<ResumeMessage> {
+        Subscription::none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ResumeMessage {
+    Resume,
+    Discard,
+}
@@ -1,68 +1,463 @@
+use std::{path::PathBuf, time::Duration};
+
 use iced::{
-    button, text_input, Align, Button, Column, Container, Element, HorizontalAlignment, Length,
-    Radio, Row, Subscription, Text, TextInput, VerticalAlignment,
+    button, scrollable, slider, text_input, Align, Button, Checkbox, Column, Container, Element,
+    HorizontalAlignment, Length, Radio, Row, Scrollable, Slider, Subscription, Text, TextInput,
+    VerticalAlignment,
+};
+use log::warn;
+
+use crate::{
+    settings::{Action, ClockSettings, PinHash, Players, Preset, Settings, Theme},
+    sound,
 };
-use std::time::Duration;
 
-use crate::settings::{ClockSettings, Settings};
+use super::{keyboard, style};
 
 pub struct SettingPane {
+    /// The settings edited, which keeps the ones only in the settings file.
+    settings: Settings,
     done_button: button::State,
-    time_limit_hour_input: text_input::State,
-    time_limit_hour_value: String,
-    time_limit_min_input: text_input::State,
-    time_limit_min_value: String,
-    time_limit_sec_input: text_input::State,
-    time_limit_sec_value: String,
+    players: Players,
+    /// The time control last parsed successfully from the shorthand or PGN field.
+    clock: ClockSettings,
+    /// The built-in presets followed by the user's ones.
+    presets: Vec<Preset>,
+    builtin_presets: usize,
+    selected_preset: Option<usize>,
+    presets_scroll: scrollable::State,
+    preset_name_input: text_input::State,
+    preset_name_value: String,
+    save_preset_button: button::State,
+    delete_preset_button: button::State,
+    pgn_input: text_input::State,
+    pgn_value: String,
+    pgn_error: Option<String>,
+    time_control_input: text_input::State,
+    time_control_value: String,
+    time_control_error: Option<String>,
+    arbiter_pin_input: text_input::State,
+    /// A new PIN typed in, which is only kept hashed in `arbiter_pin`.
+    arbiter_pin_value: String,
+    arbiter_pin: Option<PinHash>,
+    replay_input: text_input::State,
+    replay_value: String,
+    replay_error: Option<String>,
+    replay_button: button::State,
+    guard_input: text_input::State,
+    guard_value: String,
+    guard_error: Option<String>,
+    volume_slider: slider::State,
+    warnings_input: text_input::State,
+    warnings_value: String,
+    warnings_error: Option<String>,
+    countdown_input: text_input::State,
+    countdown_value: String,
+    countdown_error: Option<String>,
+    pack_input: text_input::State,
+    pack_value: String,
+    pack_error: Option<String>,
+    /// The names of the user themes followed by the built-in ones.
+    themes: Vec<String>,
+    /// The text inputs of the keys bound to each of `Action::ALL`.
+    binding_inputs: Vec<text_input::State>,
+    binding_values: Vec<String>,
+    binding_errors: Vec<Option<String>>,
 }
 
 impl SettingPane {
-    pub fn new() -> Self {
+    /// Creates a pane whose fields are filled with `settings`.
+    pub fn new(settings: &Settings) -> Self {
+        let mut presets = Preset::builtin();
+        let builtin_presets = presets.len();
+        presets.extend(Preset::load_user());
+        let selected_preset = presets
+            .iter()
+            .position(|preset| preset.clock == settings.clock);
         Self {
+            settings: settings.clone(),
             done_button: button::State::new(),
-            time_limit_hour_input: text_input::State::new(),
-            time_limit_hour_value: String::new(),
-            time_limit_min_input: text_input::State::new(),
-            time_limit_min_value: String::new(),
-            time_limit_sec_input: text_input::State::new(),
-            time_limit_sec_value: String::new(),
+            players: settings.players,
+            clock: settings.clock.clone(),
+            presets,
+            builtin_presets,
+            selected_preset,
+            presets_scroll: scrollable::State::new(),
+            preset_name_input: text_input::State::new(),
+            preset_name_value: String::new(),
+            save_preset_button: button::State::new(),
+            delete_preset_button: button::State::new(),
+            pgn_input: text_input::State::new(),
+            pgn_value: settings.clock.to_pgn(),
+            pgn_error: None,
+            time_control_input: text_input::State::new(),
+            time_control_value: settings.clock.to_shorthand(),
+            time_control_error: None,
+            arbiter_pin_input: text_input::State::new(),
+            arbiter_pin_value: String::new(),
+            arbiter_pin: settings.arbiter_pin.clone(),
+            replay_input: text_input::State::new(),
+            replay_value: String::new(),
+            replay_error: None,
+            replay_button: button::State::new(),
+            guard_input: text_input::State::new(),
+            guard_value: settings.press.guard.as_millis().to_string(),
+            guard_error: None,
+            volume_slider: slider::State::new(),
+            warnings_input: text_input::State::new(),
+            warnings_value: settings
+                .sound
+                .warnings
+                .iter()
+                .map(|warning| warning.as_secs().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            warnings_error: None,
+            countdown_input: text_input::State::new(),
+            countdown_value: settings.sound.countdown.as_secs().to_string(),
+            countdown_error: None,
+            pack_input: text_input::State::new(),
+            pack_value: settings
+                .sound
+                .pack
+                .as_ref()
+                .map(|pack| pack.display().to_string())
+                .unwrap_or_default(),
+            pack_error: None,
+            themes: settings
+                .available_themes()
+                .into_iter()
+                .map(|theme| theme.name)
+                .collect(),
+            binding_inputs: Action::ALL
+                .iter()
+                .map(|_| text_input::State::new())
+                .collect(),
+            binding_values: Action::ALL
+                .iter()
+                .map(|&action| settings.bindings.keys(action).join(", "))
+                .collect(),
+            binding_errors: vec![None; Action::ALL.len()],
+        }
+    }
+
+    /// Shows `clock` in both notations and selects the preset with it if any.
+    fn set_clock(&mut self, clock: ClockSettings) {
+        self.time_control_value = clock.to_shorthand();
+        self.time_control_error = None;
+        self.pgn_value = clock.to_pgn();
+        self.pgn_error = None;
+        self.selected_preset = self.presets.iter().position(|preset| preset.clock == clock);
+        self.clock = clock;
+    }
+
+    fn selected_theme(&self) -> Option<usize> {
+        let name = self.settings.theme.as_deref().unwrap_or("light");
+        self.themes.iter().position(|theme| theme == name)
+    }
+
+    fn has_error(&self) -> bool {
+        self.time_control_error.is_some()
+            || self.pgn_error.is_some()
+            || self.guard_error.is_some()
+            || self.warnings_error.is_some()
+            || self.countdown_error.is_some()
+            || self.pack_error.is_some()
+            || self.binding_errors.iter().any(Option::is_some)
+    }
+
+    pub fn set_replay_error(&mut self, error: String) {
+        self.replay_error = Some(error);
+    }
+
+    fn is_user_preset(&self, index: usize) -> bool {
+        index >= self.builtin_presets && index < self.presets.len()
+    }
+
+    fn save_user_presets(&self) {
+        if let Err(e) = Preset::save_user(&self.presets[self.builtin_presets..]) {
+            warn!("Failed to save the presets: {:#}", e);
         }
     }
 
     pub fn update(&mut self, message: SettingMessage) {
         match message {
-            SettingMessage::TimeLimitHourChanged(hour) => {
-                if hour.is_empty() {
-                    self.time_limit_hour_value = String::new();
-                } else {
-                    if hour.trim().parse::<u8>().is_ok() {
-                        self.time_limit_hour_value = hour;
+            SettingMessage::PlayersSelected(players) => {
+                self.players = players;
+            }
+            SettingMessage::TimeControlChanged(time_control) => {
+                match ClockSettings::from_shorthand(&time_control) {
+                    Ok(clock) => {
+                        self.set_clock(clock);
+                    }
+                    Err(e) => {
+                        self.time_control_error = Some(e.to_string());
                     }
                 }
+                self.time_control_value = time_control;
             }
-            SettingMessage::TimeLimitMinChanged(min) => {
-                if min.is_empty() {
-                    self.time_limit_min_value = String::new();
+            SettingMessage::ArbiterPinChanged(pin) => {
+                self.arbiter_pin = if pin.is_empty() {
+                    self.settings.arbiter_pin.clone()
                 } else {
-                    if min.trim().parse::<u16>().is_ok() {
-                        self.time_limit_min_value = min;
+                    Some(PinHash::new(&pin))
+                };
+                self.arbiter_pin_value = pin;
+            }
+            SettingMessage::PresetSelected(index) => {
+                if let Some(clock) = self.presets.get(index).map(|preset| preset.clock.clone()) {
+                    self.set_clock(clock);
+                    self.selected_preset = Some(index);
+                }
+            }
+            SettingMessage::PgnChanged(pgn) => {
+                match ClockSettings::from_pgn(&pgn) {
+                    Ok(clock) => {
+                        self.set_clock(clock);
+                    }
+                    Err(e) => {
+                        self.pgn_error = Some(e.to_string());
+                    }
+                }
+                self.pgn_value = pgn;
+            }
+            SettingMessage::GuardChanged(guard) => {
+                match guard.trim().parse::<u64>() {
+                    Ok(millis) => {
+                        self.settings.press.guard = Duration::from_millis(millis);
+                        self.guard_error = None;
+                    }
+                    Err(e) => {
+                        self.guard_error = Some(format!("invalid number of milliseconds: {}", e));
+                    }
+                }
+                self.guard_value = guard;
+            }
+            SettingMessage::StrictPressToggled(strict) => {
+                self.settings.press.strict = strict;
+            }
+            SettingMessage::SoundToggled(enabled) => {
+                self.settings.sound.enabled = enabled;
+            }
+            SettingMessage::ThemeSelected(index) => {
+                self.settings.theme = self.themes.get(index).cloned();
+            }
+            SettingMessage::VoiceToggled(enabled) => {
+                self.settings.sound.voice.enabled = enabled;
+            }
+            SettingMessage::VolumeChanged(volume) => {
+                self.settings.sound.volume = volume;
+            }
+            SettingMessage::WarningsChanged(warnings) => {
+                let parsed: Result<Vec<_>, _> = warnings
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|warning| !warning.is_empty())
+                    .map(|warning| warning.parse::<u64>().map(Duration::from_secs))
+                    .collect();
+                match parsed {
+                    Ok(parsed) => {
+                        self.settings.sound.warnings = parsed;
+                        self.warnings_error = None;
+                    }
+                    Err(e) => {
+                        self.warnings_error = Some(format!("invalid number of seconds: {}", e));
                     }
                 }
+                self.warnings_value = warnings;
             }
-            SettingMessage::TimeLimitSecChanged(sec) => {
-                if sec.is_empty() {
-                    self.time_limit_sec_value = String::new();
+            SettingMessage::CountdownChanged(countdown) => {
+                match countdown.trim().parse::<u64>() {
+                    Ok(secs) => {
+                        self.settings.sound.countdown = Duration::from_secs(secs);
+                        self.countdown_error = None;
+                    }
+                    Err(e) => {
+                        self.countdown_error = Some(format!("invalid number of seconds: {}", e));
+                    }
+                }
+                self.countdown_value = countdown;
+            }
+            SettingMessage::PackChanged(pack) => {
+                let dir = PathBuf::from(pack.trim());
+                if pack.trim().is_empty() {
+                    self.settings.sound.pack = None;
+                    self.pack_error = None;
+                } else if dir.join(sound::MANIFEST).is_file() {
+                    self.settings.sound.pack = Some(dir);
+                    self.pack_error = None;
                 } else {
-                    if sec.trim().parse::<u16>().is_ok() {
-                        self.time_limit_sec_value = sec;
+                    self.pack_error = Some(format!("no {} in this directory", sound::MANIFEST));
+                }
+                self.pack_value = pack;
+            }
+            SettingMessage::BindingChanged(action, value) => {
+                let index = Action::ALL
+                    .iter()
+                    .position(|&other| other == action)
+                    .unwrap_or_default();
+                let keys: Vec<_> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                let unknown: Vec<_> = keys
+                    .iter()
+                    .filter(|key| !keyboard::is_valid(key))
+                    .map(String::as_str)
+                    .collect();
+                // The first action bound to a key would silently win, so a key may have only one.
+                let taken: Vec<_> = keys
+                    .iter()
+                    .filter_map(|key| {
+                        self.settings
+                            .bindings
+                            .conflict(action, key)
+                            .map(|other| format!("{} ({})", key, other.label()))
+                    })
+                    .collect();
+                self.binding_errors[index] = if !unknown.is_empty() {
+                    Some(format!("unknown keys: {}", unknown.join(", ")))
+                } else if !taken.is_empty() {
+                    Some(format!("keys already bound: {}", taken.join(", ")))
+                } else {
+                    *self.settings.bindings.keys_mut(action) = keys;
+                    None
+                };
+                self.binding_values[index] = value;
+            }
+            SettingMessage::ReplayPathChanged(path) => {
+                self.replay_value = path;
+                self.replay_error = None;
+            }
+            SettingMessage::PresetNameChanged(name) => {
+                self.preset_name_value = name;
+            }
+            SettingMessage::SavePreset => {
+                let name = self.preset_name_value.trim().to_string();
+                let preset = Preset::new(name.clone(), self.clock.clone());
+                // A user preset with the same name is overwritten.
+                match (self.builtin_presets..self.presets.len())
+                    .find(|&index| self.presets[index].name == name)
+                {
+                    Some(index) => {
+                        self.presets[index] = preset;
+                        self.selected_preset = Some(index);
                     }
+                    None => {
+                        self.presets.push(preset);
+                        self.selected_preset = Some(self.presets.len() - 1);
+                    }
+                }
+                self.preset_name_value.clear();
+                self.save_user_presets();
+            }
+            SettingMessage::DeletePreset => {
+                if let Some(index) = self.selected_preset.filter(|&i| self.is_user_preset(i)) {
+                    self.presets.remove(index);
+                    self.selected_preset = None;
+                    self.save_user_presets();
                 }
             }
             _ => {}
         }
     }
 
-    pub fn view(&mut self) -> Element<SettingMessage> {
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, SettingMessage> {
+        let done = SettingMessage::Done(Settings {
+            clock: self.clock.clone(),
+            players: self.players,
+            arbiter_pin: self.arbiter_pin.clone(),
+            ..self.settings.clone()
+        });
+
+        let selected_theme = self.selected_theme();
+        let themes = self.themes.iter().enumerate().fold(
+            Row::new()
+                .push(Text::new("theme: "))
+                .spacing(10)
+                .align_items(Align::Center),
+            |row, (index, name)| {
+                row.push(Radio::new(
+                    index,
+                    name,
+                    selected_theme,
+                    SettingMessage::ThemeSelected,
+                ))
+            },
+        );
+        let selected_preset = self.selected_preset;
+        let deletable = matches!(selected_preset, Some(index) if self.is_user_preset(index));
+        let has_error = self.has_error();
+        let presets = self.presets.iter().enumerate().fold(
+            Scrollable::new(&mut self.presets_scroll).height(Length::Units(200)),
+            |scrollable, (index, preset)| {
+                scrollable.push(Radio::new(
+                    index,
+                    &preset.name,
+                    selected_preset,
+                    SettingMessage::PresetSelected,
+                ))
+            },
+        );
+        let mut save_preset_button =
+            Button::new(&mut self.save_preset_button, Text::new("Save preset"))
+                .style(style::button(theme));
+        if !has_error && !self.preset_name_value.trim().is_empty() {
+            save_preset_button = save_preset_button.on_press(SettingMessage::SavePreset);
+        }
+        let mut delete_preset_button =
+            Button::new(&mut self.delete_preset_button, Text::new("Delete preset"))
+                .style(style::button(theme));
+        if deletable {
+            delete_preset_button = delete_preset_button.on_press(SettingMessage::DeletePreset);
+        }
+
+        let bindings = Action::ALL
+            .iter()
+            .zip(self.binding_inputs.iter_mut())
+            .zip(self.binding_values.iter().zip(self.binding_errors.iter()))
+            .fold(
+                Column::new().push(Text::new("keys (e.g. LShift, A)")),
+                |column, ((&action, input), (value, error))| {
+                    column.push(
+                        Row::new()
+                            .push(
+                                Text::new(format!("{}: ", action.label()))
+                                    .vertical_alignment(VerticalAlignment::Center),
+                            )
+                            .push(
+                                TextInput::new(input, "none", value, move |value| {
+                                    SettingMessage::BindingChanged(action, value)
+                                })
+                                .style(style::text_input(theme)),
+                            )
+                            .push(Text::new(error.as_deref().unwrap_or("")))
+                            .align_items(Align::Center),
+                    )
+                },
+            );
+
+        let mut replay_button =
+            Button::new(&mut self.replay_button, Text::new("Replay")).style(style::button(theme));
+        if !self.replay_value.trim().is_empty() {
+            replay_button = replay_button
+                .on_press(SettingMessage::Replay(self.replay_value.trim().to_string()));
+        }
+
+        let mut done_button = Button::new(
+            &mut self.done_button,
+            Text::new("Done")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .vertical_alignment(VerticalAlignment::Center),
+        )
+        .style(style::button(theme));
+        // Never start a game with a time control the user hasn't meant.
+        if !has_error {
+            done_button = done_button.on_press(done);
+        }
+
         Container::new(
             Column::new()
                 .push(
@@ -78,9 +473,31 @@ impl SettingPane {
                                     .push(Radio::new(
                                         Players::One,
                                         "1",
-                                        Some(Players::One),
+                                        Some(self.players),
                                         SettingMessage::PlayersSelected,
                                     ))
+                                    .push(Radio::new(
+                                        Players::Two,
+                                        "2",
+                                        Some(self.players),
+                                        SettingMessage::PlayersSelected,
+                                    ))
+                                    .align_items(Align::Center),
+                            )
+                            .push(presets)
+                            .push(
+                                Row::new()
+                                    .push(
+                                        TextInput::new(
+                                            &mut self.preset_name_input,
+                                            "preset name",
+                                            &self.preset_name_value,
+                                            SettingMessage::PresetNameChanged,
+                                        )
+                                        .style(style::text_input(theme)),
+                                    )
+                                    .push(save_preset_button)
+                                    .push(delete_preset_button)
                                     .align_items(Align::Center),
                             )
                             .push(
@@ -96,65 +513,176 @@ impl SettingPane {
                             .push(
                                 Row::new()
                                     .push(
-                                        Text::new("time limit: ")
+                                        Text::new("time control: ")
                                             .vertical_alignment(VerticalAlignment::Center),
                                     )
-                                    .push(TextInput::new(
-                                        &mut self.time_limit_hour_input,
-                                        "hour",
-                                        &self.time_limit_hour_value,
-                                        SettingMessage::TimeLimitHourChanged,
-                                    ))
                                     .push(
-                                        Text::new(":")
+                                        TextInput::new(
+                                            &mut self.time_control_input,
+                                            "e.g. 5+3, G/60;d5, 40/90, SD/30+30 or 60m+5x30s",
+                                            &self.time_control_value,
+                                            SettingMessage::TimeControlChanged,
+                                        )
+                                        .style(style::text_input(theme)),
+                                    )
+                                    .align_items(Align::Center),
+                            )
+                            .push(Text::new(self.time_control_error.as_deref().unwrap_or("")))
+                            .push(
+                                Row::new()
+                                    .push(
+                                        Text::new("PGN TimeControl: ")
                                             .vertical_alignment(VerticalAlignment::Center),
                                     )
-                                    .push(TextInput::new(
-                                        &mut self.time_limit_min_input,
-                                        "minute",
-                                        &self.time_limit_min_value,
-                                        SettingMessage::TimeLimitMinChanged,
+                                    .push(
+                                        TextInput::new(
+                                            &mut self.pgn_input,
+                                            "e.g. 40/5400+30:1800+30",
+                                            &self.pgn_value,
+                                            SettingMessage::PgnChanged,
+                                        )
+                                        .style(style::text_input(theme)),
+                                    )
+                                    .align_items(Align::Center),
+                            )
+                            .push(Text::new(self.pgn_error.as_deref().unwrap_or("")))
+                            .push(
+                                Row::new()
+                                    .push(
+                                        Text::new("arbiter PIN: ")
+                                            .vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .push(
+                                        TextInput::new(
+                                            &mut self.arbiter_pin_input,
+                                            if self.settings.arbiter_pin.is_some() {
+                                                "unchanged"
+                                            } else {
+                                                "required by the arbiter pane"
+                                            },
+                                            &self.arbiter_pin_value,
+                                            SettingMessage::ArbiterPinChanged,
+                                        )
+                                        .style(style::text_input(theme))
+                                        .password(),
+                                    ),
+                            )
+                            .push(
+                                Row::new()
+                                    .push(
+                                        Text::new("ignore presses after a switch for (ms): ")
+                                            .vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .push(
+                                        TextInput::new(
+                                            &mut self.guard_input,
+                                            "0",
+                                            &self.guard_value,
+                                            SettingMessage::GuardChanged,
+                                        )
+                                        .style(style::text_input(theme)),
+                                    )
+                                    .push(Text::new(self.guard_error.as_deref().unwrap_or("")))
+                                    .align_items(Align::Center),
+                            )
+                            .push(Checkbox::new(
+                                self.settings.press.strict,
+                                "only the player to move can press",
+                                SettingMessage::StrictPressToggled,
+                            ))
+                            .push(themes)
+                            .push(
+                                Row::new()
+                                    .push(Checkbox::new(
+                                        self.settings.sound.enabled,
+                                        "sound, volume: ",
+                                        SettingMessage::SoundToggled,
+                                    ))
+                                    .push(Slider::new(
+                                        &mut self.volume_slider,
+                                        0.0..=1.0,
+                                        self.settings.sound.volume,
+                                        SettingMessage::VolumeChanged,
                                     ))
+                                    .align_items(Align::Center),
+                            )
+                            .push(Checkbox::new(
+                                self.settings.sound.voice.enabled,
+                                "read the seconds in byo-yomi",
+                                SettingMessage::VoiceToggled,
+                            ))
+                            .push(
+                                Row::new()
+                                    .push(
+                                        Text::new("warn at (s): ")
+                                            .vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .push(
+                                        TextInput::new(
+                                            &mut self.warnings_input,
+                                            "e.g. 60, 30, 10",
+                                            &self.warnings_value,
+                                            SettingMessage::WarningsChanged,
+                                        )
+                                        .style(style::text_input(theme)),
+                                    )
+                                    .push(Text::new(self.warnings_error.as_deref().unwrap_or("")))
+                                    .push(
+                                        Text::new(" tick every second from (s): ")
+                                            .vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .push(
+                                        TextInput::new(
+                                            &mut self.countdown_input,
+                                            "0",
+                                            &self.countdown_value,
+                                            SettingMessage::CountdownChanged,
+                                        )
+                                        .style(style::text_input(theme)),
+                                    )
+                                    .push(Text::new(self.countdown_error.as_deref().unwrap_or("")))
+                                    .align_items(Align::Center),
+                            )
+                            .push(
+                                Row::new()
                                     .push(
-                                        Text::new(":")
+                                        Text::new("sound pack: ")
                                             .vertical_alignment(VerticalAlignment::Center),
                                     )
-                                    .push(TextInput::new(
-                                        &mut self.time_limit_sec_input,
-                                        "second",
-                                        &self.time_limit_sec_value,
-                                        SettingMessage::TimeLimitSecChanged,
-                                    )),
+                                    .push(
+                                        TextInput::new(
+                                            &mut self.pack_input,
+                                            "built-in",
+                                            &self.pack_value,
+                                            SettingMessage::PackChanged,
+                                        )
+                                        .style(style::text_input(theme)),
+                                    )
+                                    .push(Text::new(self.pack_error.as_deref().unwrap_or("")))
+                                    .align_items(Align::Center),
                             )
+                            .push(bindings)
                             .align_items(Align::Center),
                     )
                     .center_x()
                     .center_y(),
                 )
+                .push(done_button)
                 .push(
-                    Button::new(
-                        &mut self.done_button,
-                        Text::new("Done")
-                            .horizontal_alignment(HorizontalAlignment::Center)
-                            .vertical_alignment(VerticalAlignment::Center),
-                    )
-                    .on_press(SettingMessage::Done(Settings {
-                        clock: ClockSettings {
-                            time_limit: Duration::new(
-                                self.time_limit_hour_value
-                                    .trim()
-                                    .parse::<u64>()
-                                    .unwrap_or(0)
-                                    * (60 * 60)
-                                    + self.time_limit_min_value.trim().parse::<u64>().unwrap_or(0)
-                                        * 60
-                                    + self.time_limit_sec_value.trim().parse::<u64>().unwrap_or(0),
-                                0,
-                            ),
-                        },
-                        ..Settings::default()
-                    })),
+                    Row::new()
+                        .push(
+                            TextInput::new(
+                                &mut self.replay_input,
+                                "game record (JSON) or PGN with %clk to replay",
+                                &self.replay_value,
+                                SettingMessage::ReplayPathChanged,
+                            )
+                            .style(style::text_input(theme)),
+                        )
+                        .push(replay_button)
+                        .align_items(Align::Center),
                 )
+                .push(Text::new(self.replay_error.as_deref().unwrap_or("")))
                 .align_items(Align::Center),
         )
         .width(Length::Fill)
@@ -169,22 +697,35 @@ impl SettingPane {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Players {
-    One,
-}
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Rule {
     AbsoluteTiming,
 }
 
+// A message lives only until it's handled, so the settings aren't worth boxing.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug)]
 pub enum SettingMessage {
     PlayersSelected(Players),
     RuleSelected(Rule),
-    TimeLimitHourChanged(String),
-    TimeLimitMinChanged(String),
-    TimeLimitSecChanged(String),
+    TimeControlChanged(String),
+    ArbiterPinChanged(String),
+    PresetSelected(usize),
+    PgnChanged(String),
+    PresetNameChanged(String),
+    SavePreset,
+    DeletePreset,
+    GuardChanged(String),
+    StrictPressToggled(bool),
+    ThemeSelected(usize),
+    SoundToggled(bool),
+    VoiceToggled(bool),
+    VolumeChanged(f32),
+    WarningsChanged(String),
+    CountdownChanged(String),
+    PackChanged(String),
+    BindingChanged(Action, String),
+    ReplayPathChanged(String),
+    Replay(String),
     Done(Settings),
 }
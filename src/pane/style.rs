@@ -0,0 +1,103 @@
+use iced::{button, container, text_input, Background, Color};
+
+use crate::settings::{Rgb, Theme};
+
+pub fn color(rgb: Rgb) -> Color {
+    Color::from_rgb8(rgb.r, rgb.g, rgb.b)
+}
+
+/// The background and the default text color of a whole pane.
+pub struct Root {
+    background: Color,
+    text: Color,
+}
+
+pub fn root(theme: &Theme) -> Root {
+    Root {
+        background: color(theme.background),
+        text: color(theme.text),
+    }
+}
+
+impl container::StyleSheet for Root {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: Some(self.text),
+            background: Some(Background::Color(self.background)),
+            ..container::Style::default()
+        }
+    }
+}
+
+pub struct Button {
+    background: Color,
+    text: Color,
+}
+
+pub fn button(theme: &Theme) -> Button {
+    Button {
+        background: color(theme.button),
+        text: color(theme.button_text),
+    }
+}
+
+impl button::StyleSheet for Button {
+    fn active(&self) -> button::Style {
+        button::Style {
+            background: Some(Background::Color(self.background)),
+            border_radius: 2,
+            text_color: self.text,
+            ..button::Style::default()
+        }
+    }
+}
+
+pub struct TextInput {
+    background: Color,
+    border: Color,
+    focused: Color,
+    placeholder: Color,
+    value: Color,
+    selection: Color,
+}
+
+pub fn text_input(theme: &Theme) -> TextInput {
+    TextInput {
+        background: color(theme.background),
+        border: color(theme.border),
+        focused: color(theme.text),
+        placeholder: color(theme.waiting),
+        value: color(theme.text),
+        selection: color(theme.highlight),
+    }
+}
+
+impl text_input::StyleSheet for TextInput {
+    fn active(&self) -> text_input::Style {
+        text_input::Style {
+            background: Background::Color(self.background),
+            border_radius: 2,
+            border_width: 1,
+            border_color: self.border,
+        }
+    }
+
+    fn focused(&self) -> text_input::Style {
+        text_input::Style {
+            border_color: self.focused,
+            ..self.active()
+        }
+    }
+
+    fn placeholder_color(&self) -> Color {
+        self.placeholder
+    }
+
+    fn value_color(&self) -> Color {
+        self.value
+    }
+
+    fn selection_color(&self) -> Color {
+        self.selection
+    }
+}
@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use iced::{
+    button, text_input, Align, Button, Column, Container, Element, HorizontalAlignment, Length,
+    Row, Subscription, Text, TextInput, VerticalAlignment,
+};
+
+use crate::{
+    game::{Game, PlayerStats},
+    settings::Theme,
+};
+
+use super::{
+    clock::format_duration,
+    lock::{Lock, LockMessage},
+    style,
+};
+
+pub struct SummaryPane {
+    back_button: button::State,
+    settings_button: button::State,
+    reset_button: button::State,
+    graph_button: button::State,
+    export_button: button::State,
+    replay_button: button::State,
+    pgn_path_input: text_input::State,
+    pgn_path_value: String,
+    merge_pgn_button: button::State,
+    /// The result of the last export.
+    status: String,
+    lock: Lock,
+}
+
+impl SummaryPane {
+    pub fn new() -> Self {
+        Self {
+            back_button: button::State::new(),
+            settings_button: button::State::new(),
+            reset_button: button::State::new(),
+            graph_button: button::State::new(),
+            export_button: button::State::new(),
+            replay_button: button::State::new(),
+            pgn_path_input: text_input::State::new(),
+            pgn_path_value: String::new(),
+            merge_pgn_button: button::State::new(),
+            status: String::new(),
+            lock: Lock::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: SummaryMessage) {
+        match message {
+            SummaryMessage::PgnPathChanged(path) => {
+                self.pgn_path_value = path;
+            }
+            SummaryMessage::Lock(message) => {
+                self.lock.update(message);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+
+    pub fn view(&mut self, game: &Game, theme: &Theme) -> Element<'_, SummaryMessage> {
+        if self.lock.is_locked() {
+            return Container::new(self.lock.view(theme).map(SummaryMessage::Lock))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        let stats = game.stats();
+        let table = game
+            .players()
+            .iter()
+            .zip(stats.iter())
+            .fold(Row::new().push(label_column()), |row, (player, stats)| {
+                row.push(stats_column(format!("{:?}", player), stats))
+            });
+
+        let mut merge_pgn_button =
+            Button::new(&mut self.merge_pgn_button, Text::new("Add clock to PGN"))
+                .style(style::button(theme));
+        if !self.pgn_path_value.trim().is_empty() {
+            merge_pgn_button = merge_pgn_button.on_press(SummaryMessage::MergePgn(
+                self.pgn_path_value.trim().to_string(),
+            ));
+        }
+
+        Container::new(
+            Column::new()
+                .push(
+                    Text::new("Summary")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .vertical_alignment(VerticalAlignment::Center),
+                )
+                .push(table)
+                .push(
+                    Row::new()
+                        .push(
+                            Button::new(&mut self.back_button, Text::new("Back"))
+                                .style(style::button(theme))
+                                .on_press(SummaryMessage::Back),
+                        )
+                        .push(
+                            Button::new(&mut self.graph_button, Text::new("Graph"))
+                                .style(style::button(theme))
+                                .on_press(SummaryMessage::Graph),
+                        )
+                        .push(
+                            Button::new(&mut self.settings_button, Text::new("Settings"))
+                                .style(style::button(theme))
+                                .on_press(SummaryMessage::Settings),
+                        )
+                        .push(
+                            Button::new(&mut self.reset_button, Text::new("Reset"))
+                                .style(style::button(theme))
+                                .on_press(SummaryMessage::Reset),
+                        )
+                        .push(
+                            Button::new(&mut self.export_button, Text::new("Export"))
+                                .style(style::button(theme))
+                                .on_press(SummaryMessage::Export),
+                        )
+                        .push(
+                            Button::new(&mut self.replay_button, Text::new("Replay"))
+                                .style(style::button(theme))
+                                .on_press(SummaryMessage::Replay),
+                        )
+                        .push(self.lock.lock_button(theme).map(SummaryMessage::Lock)),
+                )
+                .push(
+                    Row::new()
+                        .push(
+                            TextInput::new(
+                                &mut self.pgn_path_input,
+                                "PGN file with the moves",
+                                &self.pgn_path_value,
+                                SummaryMessage::PgnPathChanged,
+                            )
+                            .style(style::text_input(theme)),
+                        )
+                        .push(merge_pgn_button)
+                        .align_items(Align::Center),
+                )
+                .push(Text::new(&self.status))
+                .align_items(Align::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<SummaryMessage> {
+        Subscription::none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SummaryMessage {
+    Back,
+    Graph,
+    Settings,
+    Reset,
+    Export,
+    Replay,
+    PgnPathChanged(String),
+    MergePgn(String),
+    Lock(LockMessage),
+}
+
+const LABELS: [&str; 7] = [
+    "",
+    "moves",
+    "total",
+    "average",
+    "median",
+    "longest",
+    "time trouble",
+];
+
+fn label_column<'a>() -> Column<'a, SummaryMessage> {
+    LABELS
+        .iter()
+        .fold(Column::new().padding(10), |column, &label| {
+            column.push(Text::new(label))
+        })
+}
+
+fn stats_column<'a>(name: String, stats: &PlayerStats) -> Column<'a, SummaryMessage> {
+    [
+        name,
+        stats.moves.to_string(),
+        format_duration(stats.total),
+        format_move_time(stats.average),
+        format_move_time(stats.median),
+        format_move_time(stats.longest),
+        format_duration(stats.time_trouble),
+    ]
+    .iter()
+    .fold(
+        Column::new().padding(10).align_items(Align::End),
+        |column, value| column.push(Text::new(value.as_str())),
+    )
+}
+
+fn format_move_time(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
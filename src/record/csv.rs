@@ -0,0 +1,74 @@
+use chrono::SecondsFormat;
+
+use super::{EventKind, Record};
+
+impl Record {
+    /// Formats the events as CSV with a header row, one column of remaining time and moves per
+    /// player.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec![
+            "time".to_string(),
+            "offset".to_string(),
+            "event".to_string(),
+            "player".to_string(),
+            "used".to_string(),
+        ];
+        for player in 1..=self.players {
+            header.push(format!("remaining {}", player));
+        }
+        for player in 1..=self.players {
+            header.push(format!("moves {}", player));
+        }
+        header.push("note".to_string());
+
+        let mut csv = row(&header);
+        for event in &self.events {
+            let mut fields = vec![
+                event.time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                format!("{:.3}", event.offset),
+                kind(event.kind).to_string(),
+                (event.player.index() + 1).to_string(),
+                event
+                    .used
+                    .map(|used| format!("{:.3}", used))
+                    .unwrap_or_default(),
+            ];
+            fields.extend(
+                event
+                    .remaining
+                    .iter()
+                    .map(|remaining| format!("{:.3}", remaining)),
+            );
+            fields.extend(event.moves.iter().map(u32::to_string));
+            fields.push(event.note.clone().unwrap_or_default());
+            csv += &row(&fields);
+        }
+        csv
+    }
+}
+
+fn kind(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Start => "start",
+        EventKind::Move => "move",
+        EventKind::Pause => "pause",
+        EventKind::Resume => "resume",
+        EventKind::Adjustment => "adjustment",
+        EventKind::Flag => "flag",
+    }
+}
+
+/// Formats a record of RFC 4180, quoting the fields which need it.
+fn row(fields: &[String]) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            if field.contains(&[',', '"', '\n', '\r'][..]) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    fields.join(",") + "\r\n"
+}
@@ -0,0 +1,250 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Local, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{self, Correction, Game, Player},
+    settings::Settings,
+};
+
+mod csv;
+mod pgn;
+mod replay;
+
+/// The timeline of a game, which is exported for spreadsheets and other tools.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Record {
+    /// The time control in the PGN `TimeControl` notation.
+    pub time_control: String,
+    pub players: usize,
+    pub events: Vec<Event>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    /// The first press, which has started the clock of the opponent.
+    Start,
+    /// A press ending a move.
+    Move,
+    Pause,
+    Resume,
+    Adjustment,
+    /// The player has run out of time.
+    Flag,
+}
+
+/// Something which has happened to the clocks.
+///
+/// Durations are in seconds.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Event {
+    pub time: DateTime<Utc>,
+    /// The time since the game has started.
+    pub offset: f64,
+    pub kind: EventKind,
+    pub player: Player,
+    /// The time used for the move, or the length of the pause.
+    pub used: Option<f64>,
+    /// The time left to each player after the event.
+    pub remaining: Vec<f64>,
+    /// The number of moves made by each player after the event.
+    pub moves: Vec<u32>,
+    /// The correction and reason of an adjustment.
+    pub note: Option<String>,
+}
+
+impl Record {
+    pub fn new(game: &Game) -> Self {
+        let history = game.history();
+        let start = history.presses().first().map(|press| press.at);
+        let event = |at: Instant,
+                     kind,
+                     player,
+                     used: Option<Duration>,
+                     remaining: &[Duration],
+                     moves: Vec<u32>,
+                     note| {
+            let event = Event {
+                time: game::system_time(at).into(),
+                offset: start
+                    .map(|start| at.saturating_duration_since(start).as_secs_f64())
+                    .unwrap_or(0.0),
+                kind,
+                player,
+                used: used.map(|used| used.as_secs_f64()),
+                remaining: remaining.iter().map(Duration::as_secs_f64).collect(),
+                moves,
+                note,
+            };
+            (at, event)
+        };
+
+        let mut events = Vec::new();
+        for press in history.presses() {
+            let kind = if press.is_move() {
+                EventKind::Move
+            } else {
+                EventKind::Start
+            };
+            events.push(event(
+                press.at,
+                kind,
+                press.player,
+                Some(press.elapsed),
+                &press.after.remaining,
+                press.after.moves.clone(),
+                None,
+            ));
+        }
+        for pause in history.pauses() {
+            let moves = moves_at(game, pause.at);
+            events.push(event(
+                pause.at,
+                EventKind::Pause,
+                pause.player,
+                pause.duration,
+                &pause.remaining,
+                moves.clone(),
+                None,
+            ));
+            if let Some(duration) = pause.duration {
+                events.push(event(
+                    pause.at + duration,
+                    EventKind::Resume,
+                    pause.player,
+                    None,
+                    &pause.remaining,
+                    moves,
+                    None,
+                ));
+            }
+        }
+        for record in history.adjustments() {
+            let adjustment = &record.adjustment;
+            let note = format!("{}: {}", describe(adjustment.correction), adjustment.reason);
+            events.push(event(
+                record.at,
+                EventKind::Adjustment,
+                adjustment.player,
+                None,
+                &record.after.remaining,
+                record.after.moves.clone(),
+                Some(note),
+            ));
+        }
+        if let Some(flag_fall) = history.flag_fall() {
+            events.push(event(
+                flag_fall.at,
+                EventKind::Flag,
+                flag_fall.player,
+                Some(flag_fall.elapsed),
+                &flag_fall.remaining,
+                moves_at(game, flag_fall.at),
+                None,
+            ));
+        }
+        // Sorting is stable, so a resume keeps coming before a press at the same instant.
+        events.sort_by_key(|(at, _)| *at);
+
+        Self {
+            time_control: game.clock_settings().to_pgn(),
+            players: game.players().len(),
+            events: events.into_iter().map(|(_, event)| event).collect(),
+        }
+    }
+
+    /// Returns the wall-clock time the game has started at, if it has.
+    pub fn started(&self) -> Option<DateTime<Utc>> {
+        self.events.first().map(|event| event.time)
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Returns the number of moves made by each player by `at`.
+fn moves_at(game: &Game, at: Instant) -> Vec<u32> {
+    game.history()
+        .presses()
+        .iter()
+        .take_while(|press| press.at <= at)
+        .last()
+        .map(|press| press.after.moves.clone())
+        .unwrap_or_else(|| vec![0; game.players().len()])
+}
+
+fn describe(correction: Correction) -> String {
+    match correction {
+        Correction::Add(duration) => format!("add {}s", duration.as_secs_f64()),
+        Correction::Subtract(duration) => format!("subtract {}s", duration.as_secs_f64()),
+        Correction::Set(duration) => format!("set to {}s", duration.as_secs_f64()),
+        Correction::SetMoves(moves) => format!("set the moves to {}", moves),
+    }
+}
+
+/// Returns the directory the records are exported to.
+pub fn export_dir(settings: &Settings) -> Option<PathBuf> {
+    settings.export_dir.clone().or_else(|| {
+        dirs::document_dir()
+            .or_else(dirs::home_dir)
+            .map(|dir| dir.join("gameclockbone"))
+    })
+}
+
+fn file_stem(record: &Record) -> String {
+    let started: DateTime<Local> = record.started().unwrap_or_else(Utc::now).into();
+    format!("game-{}", started.format("%Y%m%d-%H%M%S"))
+}
+
+/// Exports the timeline of `game` to a JSON, a CSV and a PGN file named after its start, and
+/// returns their paths.
+pub fn export(game: &Game, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let record = Record::new(game);
+    let stem = file_stem(&record);
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    let files = vec![
+        (dir.join(&stem).with_extension("json"), record.to_json()?),
+        (dir.join(&stem).with_extension("csv"), record.to_csv()),
+        (dir.join(&stem).with_extension("pgn"), record.to_pgn()),
+    ];
+    let mut paths = Vec::new();
+    for (path, contents) in files {
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        info!("The record has been exported to {}.", path.display());
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Adds the clock annotations of `game` to the PGN file `source` and writes the result to `dir`,
+/// leaving `source` as it is. Returns the path written.
+pub fn merge_pgn(game: &Game, source: &Path, dir: &Path) -> anyhow::Result<PathBuf> {
+    let pgn = fs::read_to_string(source)
+        .with_context(|| format!("failed to read {}", source.display()))?;
+    let record = Record::new(game);
+    let merged = record.merge_pgn(&pgn)?;
+
+    let stem = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file_stem(&record));
+    let path = dir.join(format!("{}-clock.pgn", stem));
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    fs::write(&path, merged).with_context(|| format!("failed to write {}", path.display()))?;
+    info!(
+        "The clock annotations have been merged into {}.",
+        path.display()
+    );
+    Ok(path)
+}
@@ -0,0 +1,427 @@
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use log::warn;
+
+use crate::{
+    game::Player,
+    settings::{ClockSettings, MAX_TIME},
+};
+
+use super::{Event, EventKind, Record};
+
+/// The clock annotations of a move.
+struct Annotation {
+    /// The time left after the move in seconds.
+    clock: f64,
+    /// The time used for the move in seconds.
+    elapsed: f64,
+}
+
+impl Annotation {
+    fn comment(&self) -> String {
+        format!(
+            "{{[%clk {}] [%emt {}]}}",
+            format_clock(self.clock),
+            format_clock(self.elapsed)
+        )
+    }
+}
+
+impl Record {
+    fn annotations(&self) -> Vec<Annotation> {
+        self.events
+            .iter()
+            .filter(|event| event.kind == EventKind::Move)
+            .map(|event| Annotation {
+                clock: event
+                    .remaining
+                    .get(event.player.index())
+                    .copied()
+                    .unwrap_or(0.0),
+                elapsed: event.used.unwrap_or(0.0),
+            })
+            .collect()
+    }
+
+    /// Returns the result in the PGN notation, which is known only after a flag fall.
+    ///
+    /// White is the player who has moved first.
+    fn result(&self) -> &'static str {
+        let white = self
+            .events
+            .iter()
+            .find(|event| event.kind == EventKind::Move)
+            .map(|event| event.player);
+        let flagged = self
+            .events
+            .iter()
+            .find(|event| event.kind == EventKind::Flag)
+            .map(|event| event.player);
+        match (white, flagged) {
+            (Some(white), Some(flagged)) if flagged == white => "0-1",
+            (Some(_), Some(_)) => "1-0",
+            _ => "*",
+        }
+    }
+
+    /// Formats the moves as a PGN game whose moves are null moves (`--`) to be replaced, each
+    /// followed by its clock annotations.
+    pub fn to_pgn(&self) -> String {
+        let date = self
+            .started()
+            .map(|started| {
+                let started: DateTime<Local> = started.into();
+                started.format("%Y.%m.%d").to_string()
+            })
+            .unwrap_or_else(|| "????.??.??".to_string());
+        let result = self.result();
+        let mut pgn = format!(
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"{}\"]\n[Round \"?\"]\n[White \"?\"]\n\
+             [Black \"?\"]\n[Result \"{}\"]\n[TimeControl \"{}\"]\n\n",
+            date, result, self.time_control
+        );
+
+        let mut tokens = Vec::new();
+        for (ply, annotation) in self.annotations().iter().enumerate() {
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            tokens.push("--".to_string());
+            tokens.push(annotation.comment());
+        }
+        tokens.push(result.to_string());
+        pgn += &wrap(&tokens);
+        pgn
+    }
+
+    /// Adds the clock annotations to the mainline moves of the first game in `pgn` and sets its
+    /// `TimeControl` tag. Existing `%clk` and `%emt` commands of the mainline are replaced.
+    pub fn merge_pgn(&self, pgn: &str) -> anyhow::Result<String> {
+        let annotations = self.annotations();
+        let (headers, movetext) = split_headers(pgn);
+        let mut merged = merge_headers(headers, &self.time_control);
+
+        let mut plies = 0;
+        for token in tokenize(movetext) {
+            match token {
+                Token::Move(token) => {
+                    merged += token;
+                    if let Some(annotation) = annotations.get(plies) {
+                        merged += " ";
+                        merged += &annotation.comment();
+                    }
+                    plies += 1;
+                }
+                Token::Comment(comment) => {
+                    let comment = strip_clock_commands(comment);
+                    if !comment.trim().is_empty() {
+                        merged += &format!("{{{}}}", comment);
+                    }
+                }
+                Token::Other(token) => {
+                    merged += token;
+                }
+            }
+        }
+
+        if plies == 0 {
+            bail!("the PGN has no moves");
+        }
+        if plies != annotations.len() {
+            warn!(
+                "The PGN has {} moves while the clock has recorded {}.",
+                plies,
+                annotations.len()
+            );
+        }
+        Ok(merged)
+    }
+}
+
+impl Record {
+    /// Reads the clock annotations of the first game in `pgn`, e.g. one downloaded from a chess
+    /// server, as a record of two players where White is the first one.
+    ///
+    /// The time used for a move is taken from its `%emt` if any, and otherwise derived from the
+    /// previous `%clk` of the player and the increment of the time control.
+    pub fn from_pgn(pgn: &str) -> anyhow::Result<Self> {
+        let (headers, movetext) = split_headers(pgn);
+        let time_control = tag(headers, "TimeControl").unwrap_or("?");
+        let clock = ClockSettings::from_pgn(time_control).ok();
+        let start = match (tag(headers, "UTCDate"), tag(headers, "UTCTime")) {
+            (Some(date), Some(time)) => {
+                NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y.%m.%d %H:%M:%S")
+                    .ok()
+                    .map(|start| Utc.from_utc_datetime(&start))
+            }
+            _ => None,
+        }
+        .unwrap_or_else(Utc::now);
+
+        // The clock and the time used for each move, as far as they are given.
+        let mut moves: Vec<(Option<f64>, Option<f64>)> = Vec::new();
+        for token in tokenize(movetext) {
+            match token {
+                Token::Move(_) => moves.push((None, None)),
+                Token::Comment(comment) => {
+                    if let Some(last) = moves.last_mut() {
+                        if let Some(clock) = command(comment, "clk") {
+                            last.0 = Some(parse_clock(clock)?);
+                        }
+                        if let Some(elapsed) = command(comment, "emt") {
+                            last.1 = Some(parse_clock(elapsed)?);
+                        }
+                    }
+                }
+                Token::Other(_) => {}
+            }
+        }
+        if moves.iter().all(|(clock, _)| clock.is_none()) {
+            bail!("the PGN has no clock annotations");
+        }
+
+        let increment = clock
+            .as_ref()
+            .and_then(|clock| clock.stage(0))
+            .map_or(0.0, |stage| stage.increment.as_secs_f64());
+        let initial = match &clock {
+            Some(clock) => clock.time_limit().as_secs_f64(),
+            None => moves
+                .iter()
+                .filter_map(|(clock, _)| *clock)
+                .next()
+                .ok_or_else(|| anyhow!("the PGN has no clock annotations"))?,
+        };
+        let event = |offset: f64, kind, player, used, remaining: &[f64], moves: &[u32]| Event {
+            time: start + chrono::Duration::milliseconds((offset * 1000.0) as i64),
+            offset,
+            kind,
+            player,
+            used,
+            remaining: remaining.to_vec(),
+            moves: moves.to_vec(),
+            note: None,
+        };
+
+        // Black has started the clock of White.
+        let mut remaining = vec![initial; 2];
+        let mut counts = vec![0; 2];
+        let mut offset = 0.0;
+        let mut events = vec![event(
+            offset,
+            EventKind::Start,
+            Player::Second,
+            Some(0.0),
+            &remaining,
+            &counts,
+        )];
+        for (ply, (clock, elapsed)) in moves.into_iter().enumerate() {
+            let player = Player::ALL[ply % 2];
+            let index = player.index();
+            let clock = clock.unwrap_or(remaining[index]);
+            let elapsed = elapsed
+                .unwrap_or(remaining[index] + increment - clock)
+                .max(0.0);
+            offset += elapsed;
+            if offset > MAX_TIME.as_secs_f64() {
+                bail!("the game is too long");
+            }
+            remaining[index] = clock;
+            counts[index] += 1;
+            events.push(event(
+                offset,
+                EventKind::Move,
+                player,
+                Some(elapsed),
+                &remaining,
+                &counts,
+            ));
+        }
+
+        Ok(Self {
+            time_control: time_control.to_string(),
+            players: 2,
+            events,
+        })
+    }
+}
+
+/// Returns the value of the tag pair `name` in `headers`.
+fn tag<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = format!("[{} \"", name);
+    headers.lines().find_map(|line| {
+        let line = line.trim();
+        if line.starts_with(&prefix) {
+            line[prefix.len()..]
+                .rsplit_once('"')
+                .map(|(value, _)| value)
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the argument of the command `[%name ...]` in a comment.
+fn command<'a>(comment: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = format!("[%{} ", name);
+    let start = comment.find(&prefix)? + prefix.len();
+    let end = comment[start..].find(']')? + start;
+    Some(comment[start..end].trim())
+}
+
+/// Parses a clock in the `h:mm:ss` notation, where the seconds may have a fraction, into
+/// seconds up to `MAX_TIME`.
+fn parse_clock(clock: &str) -> anyhow::Result<f64> {
+    clock
+        .split(':')
+        .try_fold(0.0, |seconds, part| {
+            part.parse::<f64>()
+                .ok()
+                .filter(|part| part.is_finite() && *part >= 0.0)
+                .map(|part| seconds * 60.0 + part)
+        })
+        .filter(|&seconds| seconds <= MAX_TIME.as_secs_f64())
+        .ok_or_else(|| anyhow!("invalid clock \"{}\"", clock))
+}
+
+/// A piece of the movetext of a PGN game.
+enum Token<'a> {
+    /// A move of the mainline, which may be glued to its number as in `1.e4`.
+    Move(&'a str),
+    /// A comment on the mainline without its braces.
+    Comment(&'a str),
+    /// Anything else, which is kept as it is: spaces, move numbers, NAGs, variations, the result
+    /// and the games after it.
+    Other(&'a str),
+}
+
+/// Splits the movetext of the first game of a PGN into tokens.
+fn tokenize(movetext: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut depth = 0;
+    let mut rest = movetext;
+    while let Some(c) = rest.chars().next() {
+        let end = match c {
+            '{' => rest.find('}').map_or(rest.len(), |end| end + 1),
+            ';' => rest.find('\n').unwrap_or(rest.len()),
+            c if c.is_whitespace() => c.len_utf8(),
+            _ => rest
+                .find(|c: char| c.is_whitespace() || "{}();".contains(c))
+                .unwrap_or(rest.len()),
+        };
+        // Parentheses and a stray closing brace are tokens of their own.
+        let (token, next) = rest.split_at(end.max(c.len_utf8()));
+        rest = next;
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        let mainline = depth == 0 && c != ')';
+        let token = match c {
+            '{' if mainline => Token::Comment(token.trim_start_matches('{').trim_end_matches('}')),
+            _ if !mainline || "{}();$".contains(c) || c.is_whitespace() => Token::Other(token),
+            _ if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) => {
+                tokens.push(Token::Other(token));
+                tokens.push(Token::Other(rest));
+                break;
+            }
+            // A move number alone, e.g. `1.` or `1...`.
+            _ if token
+                .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.')
+                .is_empty() =>
+            {
+                Token::Other(token)
+            }
+            _ => Token::Move(token),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Splits a PGN into the tag pairs of the first game and the rest.
+fn split_headers(pgn: &str) -> (&str, &str) {
+    let mut end = 0;
+    while end < pgn.len() {
+        let line_end = pgn[end..].find('\n').map_or(pgn.len(), |i| end + i + 1);
+        let line = pgn[end..line_end].trim();
+        if !line.starts_with('[') && !line.is_empty() {
+            break;
+        }
+        end = line_end;
+    }
+    pgn.split_at(end)
+}
+
+/// Replaces the `TimeControl` tag pair in `headers`, or adds one after the others.
+fn merge_headers(headers: &str, time_control: &str) -> String {
+    let tag = format!("[TimeControl \"{}\"]", time_control);
+    let mut merged = String::new();
+    let mut replaced = false;
+    for line in headers.lines() {
+        if line.trim_start().starts_with("[TimeControl ") {
+            merged += &tag;
+            replaced = true;
+        } else if line.trim().is_empty() {
+            continue;
+        } else {
+            merged += line;
+        }
+        merged += "\n";
+    }
+    if !replaced {
+        merged += &tag;
+        merged += "\n";
+    }
+    merged += "\n";
+    merged
+}
+
+/// Removes the `[%clk ...]` and `[%emt ...]` commands from a comment.
+fn strip_clock_commands(comment: &str) -> String {
+    let mut stripped = String::new();
+    let mut rest = comment;
+    while let Some(start) = ["[%clk ", "[%emt "]
+        .iter()
+        .filter_map(|command| rest.find(command))
+        .min()
+    {
+        stripped += &rest[..start];
+        rest = rest[start..]
+            .find(']')
+            .map_or("", |end| &rest[start + end + 1..]);
+    }
+    stripped += rest;
+    stripped
+}
+
+/// Formats seconds as `h:mm:ss`, rounding down.
+fn format_clock(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Joins the tokens of a movetext into lines of at most 80 characters as the PGN standard
+/// recommends.
+fn wrap(tokens: &[String]) -> String {
+    let mut text = String::new();
+    let mut line = 0;
+    for token in tokens {
+        if line > 0 && line + 1 + token.len() > 80 {
+            text += "\n";
+            line = 0;
+        } else if line > 0 {
+            text += " ";
+            line += 1;
+        }
+        text += token;
+        line += token.len();
+    }
+    text + "\n"
+}
@@ -0,0 +1,102 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context};
+
+use crate::{
+    game::Player,
+    settings::{Players, MAX_TIME},
+};
+
+use super::{EventKind, Record};
+
+/// The clocks at an instant of a recorded game.
+#[derive(Clone, Debug)]
+pub struct Position {
+    /// The time left to each player in seconds.
+    pub remaining: Vec<f64>,
+    pub moves: Vec<u32>,
+    /// The player whose clock is counting down, if any.
+    pub running: Option<Player>,
+}
+
+impl Record {
+    /// Loads a record exported to JSON, or the clock annotations of a PGN file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let is_pgn = matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some(extension) if extension.eq_ignore_ascii_case("pgn")
+        );
+        let record = if is_pgn {
+            Self::from_pgn(&contents)?
+        } else {
+            serde_json::from_str::<Self>(&contents)?
+        };
+        record.check()?;
+        Ok(record)
+    }
+
+    /// Checks that the times of the events are between zero and `MAX_TIME`, so that they can be
+    /// replayed.
+    fn check(&self) -> anyhow::Result<()> {
+        let valid = |secs: f64| (0.0..=MAX_TIME.as_secs_f64()).contains(&secs);
+        for event in &self.events {
+            if !valid(event.offset)
+                || !event.used.is_none_or(valid)
+                || !event.remaining.iter().all(|&remaining| valid(remaining))
+            {
+                bail!("the event at {} has an invalid time", event.time);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the time from the start of the game to the last event in seconds.
+    pub fn length(&self) -> f64 {
+        self.events.last().map_or(0.0, |event| event.offset)
+    }
+
+    /// Returns the clocks `offset` seconds after the start of the game.
+    ///
+    /// The running clock is counted down from the last event, ignoring the delay if any.
+    pub fn position(&self, offset: f64) -> Position {
+        let players = if self.players == 1 {
+            Players::One
+        } else {
+            Players::Two
+        };
+        let mut position = Position {
+            remaining: self
+                .events
+                .first()
+                .map(|event| event.remaining.clone())
+                .unwrap_or_default(),
+            moves: vec![0; self.players],
+            running: None,
+        };
+        let mut since = 0.0;
+        for event in self
+            .events
+            .iter()
+            .take_while(|event| event.offset <= offset)
+        {
+            position.remaining = event.remaining.clone();
+            position.moves = event.moves.clone();
+            since = event.offset;
+            position.running = match event.kind {
+                EventKind::Start | EventKind::Move => Some(event.player.opponent(players)),
+                EventKind::Resume => Some(event.player),
+                EventKind::Pause | EventKind::Flag => None,
+                EventKind::Adjustment => position.running,
+            };
+        }
+        if let Some(remaining) = position
+            .running
+            .and_then(|player| position.remaining.get_mut(player.index()))
+        {
+            *remaining = (*remaining - (offset - since)).max(0.0);
+        }
+        position
+    }
+}
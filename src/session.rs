@@ -0,0 +1,130 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{game::Game, settings::Settings};
+
+/// The version of the schema of the session file.
+const VERSION: i64 = 2;
+
+/// A game in progress saved so that it can be resumed after a crash.
+#[derive(Deserialize, Serialize)]
+pub struct Session {
+    version: i64,
+    /// The settings the game has been started with, which may differ from the saved ones.
+    pub settings: Settings,
+    /// The game, which is always saved suspended.
+    pub game: Game,
+}
+
+impl Session {
+    pub fn new(mut settings: Settings, game: Game) -> Self {
+        // The PIN stays in the settings file only; the one there applies on resume.
+        settings.arbiter_pin = None;
+        Self {
+            version: VERSION,
+            settings,
+            game,
+        }
+    }
+}
+
+/// Returns the path of the session file in the state directory of this app, or in the local data
+/// directory on platforms without one.
+fn path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("gameclockbone").join("session.json"))
+}
+
+/// Loads the game interrupted last time, if any.
+pub fn load() -> Option<Session> {
+    let path = path()?;
+    if !path.is_file() {
+        debug!("{} doesn't exist; no game to resume.", path.display());
+        return None;
+    }
+
+    match read(&path) {
+        Ok(session) => {
+            info!(
+                "An interrupted game has been loaded from {}.",
+                path.display()
+            );
+            Some(session)
+        }
+        Err(e) => {
+            // Keep the broken file for a dispute, since it is overwritten by the next game.
+            let backup = path.with_extension("json.bak");
+            warn!(
+                "Failed to load {} ({:#}); move it to {}.",
+                path.display(),
+                e,
+                backup.display()
+            );
+            if let Err(e) = fs::rename(&path, &backup) {
+                warn!("Failed to move {}: {}", path.display(), e);
+            }
+            None
+        }
+    }
+}
+
+fn read(path: &Path) -> anyhow::Result<Session> {
+    let mut value: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    match value.get("version").and_then(serde_json::Value::as_i64) {
+        Some(1) => {
+            // Version 1 has saved the arbiter PIN in plain text.
+            if let Some(settings) = value
+                .get_mut("settings")
+                .and_then(serde_json::Value::as_object_mut)
+            {
+                settings.remove("arbiter_pin");
+            }
+            value["version"] = serde_json::Value::from(VERSION);
+            Ok(serde_json::from_value(value)?)
+        }
+        Some(VERSION) => Ok(serde_json::from_value(value)?),
+        Some(version) => bail!("unsupported version: {}", version),
+        None => bail!("the version is missing"),
+    }
+}
+
+/// Saves `session`, replacing the previous one atomically so that a crash while writing never
+/// leaves a truncated file.
+pub fn save(session: &Session) -> anyhow::Result<()> {
+    let path = path().ok_or_else(|| anyhow!("the state directory isn't available"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, serde_json::to_string(session)?)
+        .with_context(|| format!("failed to write {}", temporary.display()))?;
+    fs::rename(&temporary, &path)
+        .with_context(|| format!("failed to replace {}", path.display()))?;
+    debug!("The session has been saved to {}.", path.display());
+    Ok(())
+}
+
+/// Removes the saved session, once the game has ended or been abandoned.
+pub fn discard() -> anyhow::Result<()> {
+    let path = match path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    match fs::remove_file(&path) {
+        Ok(()) => {
+            info!("The session {} has been discarded.", path.display());
+            Ok(())
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("failed to remove {}", path.display())),
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+/// An action which can be bound to keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Presses the clock as the first player, i.e. ends their move.
+    PressFirst,
+    PressSecond,
+    /// Presses the clock as the player to move.
+    PressActive,
+    /// Pauses the game, or resumes it from the pause menu.
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [
+        Action::PressFirst,
+        Action::PressSecond,
+        Action::PressActive,
+        Action::Pause,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::PressFirst => "first player",
+            Action::PressSecond => "second player",
+            Action::PressActive => "player to move",
+            Action::Pause => "pause",
+        }
+    }
+}
+
+/// The keys bound to each action, named as the variants of `iced_native`'s `KeyCode`, e.g.
+/// `LShift`, `A` or `Space`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub first: Vec<String>,
+    pub second: Vec<String>,
+    pub active: Vec<String>,
+    pub pause: Vec<String>,
+}
+
+impl Bindings {
+    pub fn keys(&self, action: Action) -> &[String] {
+        match action {
+            Action::PressFirst => &self.first,
+            Action::PressSecond => &self.second,
+            Action::PressActive => &self.active,
+            Action::Pause => &self.pause,
+        }
+    }
+
+    pub fn keys_mut(&mut self, action: Action) -> &mut Vec<String> {
+        match action {
+            Action::PressFirst => &mut self.first,
+            Action::PressSecond => &mut self.second,
+            Action::PressActive => &mut self.active,
+            Action::Pause => &mut self.pause,
+        }
+    }
+
+    /// Returns the action bound to the key named `key`, ignoring the case.
+    pub fn action(&self, key: &str) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|&action| self.is_bound(action, key))
+    }
+
+    /// Returns an action other than `action` which the key named `key` is bound to, if any.
+    pub fn conflict(&self, action: Action, key: &str) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|&other| other != action && self.is_bound(other, key))
+    }
+
+    fn is_bound(&self, action: Action, key: &str) -> bool {
+        self.keys(action)
+            .iter()
+            .any(|bound| bound.eq_ignore_ascii_case(key))
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect();
+        Self {
+            first: keys(&["LShift", "A"]),
+            second: keys(&["RShift", "L"]),
+            active: keys(&["Space"]),
+            pause: keys(&["P", "Escape"]),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::{convert::TryFrom, fmt};
+
+use serde::{Deserialize, Serialize};
+
+/// A color written as in CSS, e.g. `#ffcc00`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0x00, 0x00, 0x00);
+    pub const WHITE: Rgb = Rgb::new(0xff, 0xff, 0xff);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+impl TryFrom<String> for Rgb {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let hex = value.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|part| u8::from_str_radix(part, 16).ok())
+        };
+        // `from_str_radix` would take a sign as in `+f`.
+        let digits = hex.bytes().all(|byte| byte.is_ascii_hexdigit());
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) if digits => Ok(Self::new(r, g, b)),
+            _ => Err(format!(
+                "invalid color \"{}\", expected e.g. #ffcc00",
+                value
+            )),
+        }
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(color: Rgb) -> Self {
+        color.to_string()
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        let parse = |value: &str| Rgb::try_from(value.to_string());
+        assert_eq!(parse("#ffcc00"), Ok(Rgb::new(0xff, 0xcc, 0x00)));
+        assert_eq!(parse("FFCC00"), Ok(Rgb::new(0xff, 0xcc, 0x00)));
+        assert_eq!(parse(" #0a0b0c "), Ok(Rgb::new(0x0a, 0x0b, 0x0c)));
+        for invalid in &[
+            "",
+            "#",
+            "#fc0",
+            "#ffcc0",
+            "#ffcc000",
+            "#ffcc0g",
+            "#+f+f+f",
+            "#ffcc\u{e9}",
+        ] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn round_trips_through_a_string() {
+        let color = Rgb::new(0x12, 0xab, 0xef);
+        assert_eq!(String::from(color), "#12abef");
+        assert_eq!(Rgb::try_from(String::from(color)), Ok(color));
+    }
+}
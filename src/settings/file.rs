@@ -0,0 +1,207 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context};
+use log::{debug, info, warn};
+use toml::{value::Table, Value};
+
+use super::{PinHash, Settings, Theme};
+
+/// The version of the schema of the settings file.
+///
+/// Bump this and add a step to `migrate` whenever the schema changes incompatibly.
+const VERSION: i64 = 4;
+
+/// The name of the user theme made of the colors in the display settings of version 3.
+const MIGRATED_THEME: &str = "custom";
+
+/// Returns the path of the file `name` in the config directory of this app.
+pub(super) fn config_path(name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("gameclockbone").join(name))
+}
+
+fn path() -> Option<PathBuf> {
+    config_path("settings.toml")
+}
+
+/// Loads the settings, falling back on the default ones if the file is missing or malformed.
+pub fn load() -> Settings {
+    let path = match path() {
+        Some(path) => path,
+        None => {
+            warn!("The config directory isn't available; use the default settings.");
+            return Settings::default();
+        }
+    };
+    if !path.is_file() {
+        debug!(
+            "{} doesn't exist; use the default settings.",
+            path.display()
+        );
+        return Settings::default();
+    }
+
+    match read(&path) {
+        Ok(settings) => {
+            info!("The settings have been loaded from {}.", path.display());
+            settings
+        }
+        Err(e) => {
+            // Keep the broken file so that it isn't lost when the settings are saved next time.
+            let backup = path.with_extension("toml.bak");
+            warn!(
+                "Failed to load {} ({:#}); use the default settings and move it to {}.",
+                path.display(),
+                e,
+                backup.display()
+            );
+            if let Err(e) = fs::rename(&path, &backup) {
+                warn!("Failed to move {}: {}", path.display(), e);
+            }
+            Settings::default()
+        }
+    }
+}
+
+fn read(path: &Path) -> anyhow::Result<Settings> {
+    let value = fs::read_to_string(path)?.parse::<Value>()?;
+    let version = match value.get("version") {
+        Some(version) => version
+            .as_integer()
+            .ok_or_else(|| anyhow!("the version isn't an integer"))?,
+        None => VERSION,
+    };
+    Ok(migrate(value, version)?.try_into()?)
+}
+
+/// Converts the settings written with the schema `version` into the current schema.
+fn migrate(mut value: Value, version: i64) -> anyhow::Result<Value> {
+    match version {
+        1 => {
+            // The single time limit has become the first of the stages.
+            if let Some(clock) = value.get_mut("clock").and_then(Value::as_table_mut) {
+                if let Some(time_limit) = clock.remove("time_limit") {
+                    let mut stage = Table::new();
+                    stage.insert("time".to_string(), time_limit);
+                    clock.insert(
+                        "stages".to_string(),
+                        Value::Array(vec![Value::Table(stage)]),
+                    );
+                }
+            }
+            migrate(value, 2)
+        }
+        2 => {
+            // The arbiter PIN has been stored in plain text.
+            if let Some(table) = value.as_table_mut() {
+                if let Some(Value::String(pin)) = table.remove("arbiter_pin") {
+                    table.insert(
+                        "arbiter_pin".to_string(),
+                        Value::String(PinHash::new(&pin).into()),
+                    );
+                }
+            }
+            migrate(value, 3)
+        }
+        3 => {
+            // The colors of the clocks have moved from the display settings into the themes, so
+            // the ones differing from the light theme make a user theme.
+            if let Some(table) = value.as_table_mut() {
+                let mut colors = Table::new();
+                if let Some(display) = table.get_mut("display").and_then(Value::as_table_mut) {
+                    for &key in &["active", "highlight", "waiting", "flag"] {
+                        if let Some(color) = display.remove(key) {
+                            colors.insert(key.to_string(), color);
+                        }
+                    }
+                }
+                let light = Value::try_from(Theme::light())?;
+                if colors
+                    .iter()
+                    .any(|(key, color)| light.get(key) != Some(color))
+                {
+                    colors.insert(
+                        "name".to_string(),
+                        Value::String(MIGRATED_THEME.to_string()),
+                    );
+                    if let Value::Array(themes) = table
+                        .entry("themes".to_string())
+                        .or_insert_with(|| Value::Array(Vec::new()))
+                    {
+                        themes.push(Value::Table(colors));
+                    }
+                    table
+                        .entry("theme".to_string())
+                        .or_insert_with(|| Value::String(MIGRATED_THEME.to_string()));
+                }
+            }
+            migrate(value, 4)
+        }
+        VERSION => Ok(value),
+        version => bail!("unsupported version: {}", version),
+    }
+}
+
+pub fn save(settings: &Settings) -> anyhow::Result<()> {
+    let path = path().ok_or_else(|| anyhow!("the config directory isn't available"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut value = Value::try_from(settings)?;
+    if let Value::Table(table) = &mut value {
+        table.insert("version".to_string(), Value::Integer(VERSION));
+    }
+    // Serializing a `Value` puts plain values before tables as TOML requires.
+    fs::write(&path, toml::to_string(&value)?)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    info!("The settings have been saved to {}.", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrate_str(settings: &str) -> Settings {
+        let value = settings.parse::<Value>().unwrap();
+        migrate(value, 3).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn moves_custom_colors_into_a_theme() {
+        let settings = migrate_str(
+            r##"
+            [display]
+            active = "#112233"
+            highlight = "#ddeeff"
+            waiting = "#808080"
+            flag = "#dd0000"
+            flash = false
+            "##,
+        );
+        assert_eq!(settings.theme.as_deref(), Some(MIGRATED_THEME));
+        let theme = settings.theme();
+        assert_eq!(theme.name, MIGRATED_THEME);
+        assert_eq!(theme.active.to_string(), "#112233");
+        assert_eq!(theme.background, Theme::light().background);
+        assert!(!settings.display.flash);
+    }
+
+    #[test]
+    fn drops_default_colors() {
+        let settings = migrate_str(
+            r##"
+            [display]
+            active = "#000000"
+            highlight = "#ddeeff"
+            waiting = "#808080"
+            flag = "#dd0000"
+            "##,
+        );
+        assert_eq!(settings.theme, None);
+        assert!(settings.themes.is_empty());
+    }
+}
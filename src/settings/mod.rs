@@ -0,0 +1,449 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+mod bindings;
+mod color;
+mod file;
+mod parse;
+mod pgn;
+mod pin;
+mod preset;
+mod shorthand;
+mod theme;
+
+pub use bindings::{Action, Bindings};
+pub use color::Rgb;
+pub use file::{load, save};
+pub use parse::MAX_TIME;
+pub use pin::PinHash;
+pub use preset::Preset;
+pub use theme::Theme;
+
+/// A period of the main time, e.g. 40 moves in 90 minutes with 30 seconds added per move.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Stage {
+    /// The number of moves to be made in this stage, or `None` for the rest of the game.
+    pub moves: Option<u32>,
+    #[serde(with = "seconds")]
+    pub time: Duration,
+    /// The time added after each move (Fischer increment).
+    #[serde(with = "seconds")]
+    pub increment: Duration,
+    /// The time at the start of each move before the clock starts counting down (simple delay).
+    #[serde(with = "seconds")]
+    pub delay: Duration,
+}
+
+impl Stage {
+    pub fn new(time: Duration) -> Self {
+        Self {
+            moves: None,
+            time,
+            increment: Duration::new(0, 0),
+            delay: Duration::new(0, 0),
+        }
+    }
+}
+
+impl Default for Stage {
+    fn default() -> Self {
+        Self::new(Duration::new(3 * 60, 0)) // Default time limit is 3min.
+    }
+}
+
+/// The time given after the main time has run out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Overtime {
+    /// Each move has to be made within `time`, and each period running out consumes one of
+    /// `periods`.
+    ByoYomi {
+        periods: u32,
+        #[serde(with = "seconds")]
+        time: Duration,
+    },
+    /// Every `moves` moves have to be made within `time`.
+    Canadian {
+        moves: u32,
+        #[serde(with = "seconds")]
+        time: Duration,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClockSettings {
+    /// The stages of the main time in order. The last one repeats if it has a number of moves.
+    pub stages: Vec<Stage>,
+    pub overtime: Option<Overtime>,
+    /// Whether the time used by a player is given to the opponent (sandclock).
+    pub hourglass: bool,
+}
+
+impl ClockSettings {
+    pub fn time_limit(&self) -> Duration {
+        self.stages
+            .first()
+            .map(|stage| stage.time)
+            .unwrap_or_else(|| Duration::new(0, 0))
+    }
+
+    /// Returns the stage at `index`, or `None` if the main time has no more stages.
+    pub fn stage(&self, index: usize) -> Option<&Stage> {
+        match self.stages.get(index) {
+            Some(stage) => Some(stage),
+            None => self.stages.last().filter(|stage| stage.moves.is_some()),
+        }
+    }
+}
+
+impl Default for ClockSettings {
+    fn default() -> Self {
+        Self {
+            stages: vec![Stage::default()],
+            overtime: None,
+            hourglass: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Players {
+    #[default]
+    One,
+    Two,
+}
+
+impl Players {
+    pub fn count(self) -> usize {
+        match self {
+            Players::One => 1,
+            Players::Two => 2,
+        }
+    }
+}
+
+/// The protection against presses nobody has meant.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PressSettings {
+    /// How long presses are ignored for after a switch, against bouncing keys and double taps.
+    #[serde(with = "seconds")]
+    pub guard: Duration,
+    /// Whether only the player to move can press the clock.
+    pub strict: bool,
+}
+
+impl Default for PressSettings {
+    fn default() -> Self {
+        Self {
+            guard: Duration::from_millis(200),
+            strict: false,
+        }
+    }
+}
+
+/// The reading of the seconds during byo-yomi.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct VoiceSettings {
+    pub enabled: bool,
+    /// The interval between the seconds read before the last ones.
+    #[serde(with = "seconds")]
+    pub every: Duration,
+    /// The time at the end of a period whose every second is counted.
+    #[serde(with = "seconds")]
+    pub count: Duration,
+    /// The directory of the recordings of the numbers, which are replaced by beeps if missing.
+    pub samples: Option<PathBuf>,
+}
+
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            every: Duration::from_secs(10),
+            count: Duration::from_secs(10),
+            samples: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub enabled: bool,
+    /// The volume between 0 and 1.
+    #[serde(deserialize_with = "deserialize_volume")]
+    pub volume: f32,
+    /// The times left at which a warning is played.
+    #[serde(
+        serialize_with = "seconds::serialize_list",
+        deserialize_with = "seconds::deserialize_list"
+    )]
+    pub warnings: Vec<Duration>,
+    /// The time left from which every second is ticked.
+    #[serde(with = "seconds")]
+    pub countdown: Duration,
+    /// The directory of the sound pack replacing the built-in sounds.
+    pub pack: Option<PathBuf>,
+    pub voice: VoiceSettings,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.8,
+            warnings: vec![
+                Duration::from_secs(60),
+                Duration::from_secs(30),
+                Duration::from_secs(10),
+            ],
+            countdown: Duration::from_secs(10),
+            pack: None,
+            voice: VoiceSettings::default(),
+        }
+    }
+}
+
+/// A color of the time left, either from the theme or given as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Tint {
+    Theme(Level),
+    Rgb(Rgb),
+}
+
+/// The low-time colors of a theme.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Warning,
+    Critical,
+}
+
+impl Tint {
+    pub fn resolve(self, theme: &Theme) -> Rgb {
+        match self {
+            Tint::Theme(Level::Warning) => theme.warning,
+            Tint::Theme(Level::Critical) => theme.critical,
+            Tint::Rgb(rgb) => rgb,
+        }
+    }
+}
+
+/// A color of the time left below a given time.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Threshold {
+    #[serde(with = "seconds")]
+    pub below: Duration,
+    /// `warning`, `critical` or a color such as `#ffcc00`.
+    pub color: Tint,
+}
+
+/// How the clock faces show low time and flag falls, in the colors of the theme, and when the
+/// summary counts a player in time trouble.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// The colors replacing the usual ones when little time is left, of which the lowest applies.
+    pub thresholds: Vec<Threshold>,
+    /// Whether the clock of the flagged player flashes.
+    pub flash: bool,
+    /// The time left below which a player is in time trouble, or `None` for a tenth of the time
+    /// limit.
+    #[serde(
+        serialize_with = "seconds::serialize_option",
+        deserialize_with = "seconds::deserialize_option"
+    )]
+    pub time_trouble: Option<Duration>,
+    /// The TrueType or OpenType font of the digits, e.g. a seven-segment one.
+    pub font: Option<PathBuf>,
+}
+
+impl DisplaySettings {
+    /// Returns the color of `remaining` time, if it is below a threshold.
+    pub fn threshold_color(&self, remaining: Duration) -> Option<Tint> {
+        self.thresholds
+            .iter()
+            .filter(|threshold| remaining < threshold.below)
+            .min_by_key(|threshold| threshold.below)
+            .map(|threshold| threshold.color)
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            thresholds: vec![
+                Threshold {
+                    below: Duration::from_secs(60),
+                    color: Tint::Theme(Level::Warning),
+                },
+                Threshold {
+                    below: Duration::from_secs(10),
+                    color: Tint::Theme(Level::Critical),
+                },
+            ],
+            flash: true,
+            time_trouble: None,
+            font: None,
+        }
+    }
+}
+
+/// How the window is opened, which takes effect at the next launch.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// The size of the window in logical pixels when it isn't fullscreen.
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub decorations: bool,
+    /// Fullscreen without decorations, resizing nor mouse cursor, for a device dedicated to the
+    /// clock.
+    pub kiosk: bool,
+    /// The scale factor of the interface, or `None` for the one of the screen.
+    pub scale: Option<f64>,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 1024,
+            height: 768,
+            fullscreen: false,
+            decorations: true,
+            kiosk: false,
+            scale: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub clock: ClockSettings,
+    pub players: Players,
+    /// The hash of the PIN protecting the arbiter's adjustments, which has to be set before
+    /// the arbiter pane can be opened.
+    pub arbiter_pin: Option<PinHash>,
+    /// The directory the game records are exported to instead of `~/Documents/gameclockbone`.
+    pub export_dir: Option<PathBuf>,
+    pub bindings: Bindings,
+    pub press: PressSettings,
+    pub sound: SoundSettings,
+    pub display: DisplaySettings,
+    /// The name of the selected theme, the light one by default.
+    pub theme: Option<String>,
+    /// The themes defined by the user, which can replace the built-in ones of the same name.
+    pub themes: Vec<Theme>,
+    pub window: WindowSettings,
+}
+
+/// Deserializes a volume, which has to be between 0 and 1.
+pub fn deserialize_volume<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let volume = f32::deserialize(deserializer)?;
+    // This rejects NaN too, which would get through `clamp` when a sound is played.
+    if (0.0..=1.0).contains(&volume) {
+        Ok(volume)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "invalid volume: {}, expected a number between 0 and 1",
+            volume
+        )))
+    }
+}
+
+/// (De)serializes a duration as a number of seconds, which is easier to edit by hand.
+mod seconds {
+    use std::time::Duration;
+
+    use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        if duration.subsec_nanos() == 0 {
+            serializer.serialize_u64(duration.as_secs())
+        } else {
+            serializer.serialize_f64(duration.as_secs_f64())
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        // Negative, infinite and too large numbers don't make a duration.
+        Duration::try_from_secs_f64(secs)
+            .map_err(|_| serde::de::Error::custom(format!("invalid number of seconds: {}", secs)))
+    }
+
+    pub fn serialize_option<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize_option<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        #[derive(Deserialize)]
+        struct Seconds(#[serde(with = "super::seconds")] Duration);
+
+        let option = Option::<Seconds>::deserialize(deserializer)?;
+        Ok(option.map(|Seconds(duration)| duration))
+    }
+
+    pub fn serialize_list<S: Serializer>(
+        durations: &[Duration],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        struct Seconds<'a>(&'a Duration);
+
+        impl serde::Serialize for Seconds<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize(self.0, serializer)
+            }
+        }
+
+        let mut seq = serializer.serialize_seq(Some(durations.len()))?;
+        for duration in durations {
+            seq.serialize_element(&Seconds(duration))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize_list<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Duration>, D::Error> {
+        #[derive(Deserialize)]
+        struct Seconds(#[serde(with = "super::seconds")] Duration);
+
+        let list = Vec::<Seconds>::deserialize(deserializer)?;
+        Ok(list.into_iter().map(|Seconds(duration)| duration).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_volumes_between_0_and_1() {
+        for &volume in &[0.0, 0.5, 1.0] {
+            let sound = toml::from_str::<SoundSettings>(&format!("volume = {:?}", volume)).unwrap();
+            assert_eq!(sound.volume, volume);
+        }
+        for volume in &["-0.5", "1.5", "nan", "inf"] {
+            let sound = toml::from_str::<SoundSettings>(&format!("volume = {}", volume));
+            assert!(sound.is_err(), "{}", volume);
+        }
+    }
+}
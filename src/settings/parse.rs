@@ -0,0 +1,77 @@
+use std::{error::Error, fmt, ops::Range, time::Duration};
+
+/// The longest time accepted in a time control, which keeps the deadlines of the clocks far from
+/// the limits of `Instant`.
+pub const MAX_TIME: Duration = Duration::from_secs(366 * 24 * 60 * 60);
+
+/// An error in a time control notation, pointing at the bad part of the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub input: String,
+    /// The range of bytes in the input that the error is about.
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(input: &str, range: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            input: input.to_string(),
+            range,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the part of the input that the error is about.
+    pub fn snippet(&self) -> &str {
+        self.input.get(self.range.clone()).unwrap_or("")
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.range.start >= self.input.len() {
+            write!(f, "{} at the end of \"{}\"", self.message, self.input)
+        } else {
+            write!(
+                f,
+                "{} at column {}: \"{}\"",
+                self.message,
+                self.range.start + 1,
+                self.snippet()
+            )
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a non-negative integer spanning the whole `part`, which starts at `start` of `input`.
+pub fn number(input: &str, part: &str, start: usize, what: &str) -> Result<u64, ParseError> {
+    let range = start..start + part.len();
+    if part.is_empty() {
+        return Err(ParseError::new(input, range, format!("missing {}", what)));
+    }
+    if !part.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::new(
+            input,
+            range,
+            format!("expected {} as a number", what),
+        ));
+    }
+    part.parse::<u64>()
+        .map_err(|_| ParseError::new(input, range, format!("too large {}", what)))
+}
+
+/// Parses a number of seconds spanning the whole `part` like `number`, up to `MAX_TIME`.
+pub fn seconds(input: &str, part: &str, start: usize, what: &str) -> Result<Duration, ParseError> {
+    let secs = number(input, part, start, what)?;
+    if secs > MAX_TIME.as_secs() {
+        return Err(ParseError::new(
+            input,
+            start..start + part.len(),
+            format!("too long {}", what),
+        ));
+    }
+    Ok(Duration::from_secs(secs))
+}
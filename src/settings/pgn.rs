@@ -0,0 +1,189 @@
+//! The `TimeControl` tag of PGN, e.g. `40/5400+30:1800+30`, `300+3` or `*180`.
+
+use std::{convert::TryFrom, time::Duration};
+
+use super::{
+    parse::{number, seconds, ParseError},
+    ClockSettings, Stage,
+};
+
+impl ClockSettings {
+    /// Parses the value of a PGN `TimeControl` tag.
+    pub fn from_pgn(input: &str) -> Result<Self, ParseError> {
+        let trimmed = input.trim_start();
+        let offset = input.len() - trimmed.len();
+        let trimmed = trimmed.trim_end();
+        if trimmed.is_empty() {
+            return Err(ParseError::new(input, 0..input.len(), "empty time control"));
+        }
+
+        let fields: Vec<(usize, &str)> = trimmed
+            .split(':')
+            .scan(offset, |start, field| {
+                let item = (*start, field);
+                *start += field.len() + 1;
+                Some(item)
+            })
+            .collect();
+        let mut settings = ClockSettings {
+            stages: Vec::new(),
+            overtime: None,
+            hourglass: false,
+        };
+        for (i, &(start, field)) in fields.iter().enumerate() {
+            let range = start..start + field.len();
+            let last = i + 1 == fields.len();
+            match field {
+                "?" => return Err(ParseError::new(input, range, "unknown time control")),
+                "-" => return Err(ParseError::new(input, range, "no time control")),
+                _ => {}
+            }
+
+            if let Some(time) = field.strip_prefix('*') {
+                if fields.len() > 1 {
+                    return Err(ParseError::new(
+                        input,
+                        range,
+                        "a sandclock can't be combined with other fields",
+                    ));
+                }
+                let time = seconds(input, time, start + 1, "the seconds of the sandclock")?;
+                settings.stages.push(Stage::new(time));
+                settings.hourglass = true;
+                continue;
+            }
+
+            let (moves, time_start, time) = match field.find('/') {
+                Some(slash) => {
+                    let range = start..start + slash;
+                    let moves = number(input, &field[..slash], start, "the number of moves")?;
+                    let moves = u32::try_from(moves).map_err(|_| {
+                        ParseError::new(input, range.clone(), "too large number of moves")
+                    })?;
+                    if moves == 0 {
+                        return Err(ParseError::new(
+                            input,
+                            range,
+                            "the number of moves must be positive",
+                        ));
+                    }
+                    (Some(moves), start + slash + 1, &field[slash + 1..])
+                }
+                None => {
+                    if !last {
+                        return Err(ParseError::new(
+                            input,
+                            range,
+                            "only the last field may have no number of moves",
+                        ));
+                    }
+                    (None, start, field)
+                }
+            };
+            let (time, increment) = match time.find('+') {
+                Some(plus) => (
+                    seconds(input, &time[..plus], time_start, "the seconds")?,
+                    seconds(
+                        input,
+                        &time[plus + 1..],
+                        time_start + plus + 1,
+                        "the seconds of the increment",
+                    )?,
+                ),
+                None => (
+                    seconds(input, time, time_start, "the seconds")?,
+                    Duration::new(0, 0),
+                ),
+            };
+            settings.stages.push(Stage {
+                moves,
+                increment,
+                ..Stage::new(time)
+            });
+        }
+        Ok(settings)
+    }
+
+    /// Formats the settings as the value of a PGN `TimeControl` tag.
+    ///
+    /// The tag has no notation for delays and overtime, so they are left out.
+    pub fn to_pgn(&self) -> String {
+        if self.hourglass {
+            return format!("*{}", self.time_limit().as_secs());
+        }
+        if self.stages.is_empty() {
+            return "-".to_string();
+        }
+        self.stages
+            .iter()
+            .map(|stage| {
+                let mut field = String::new();
+                if let Some(moves) = stage.moves {
+                    field.push_str(&format!("{}/", moves));
+                }
+                field.push_str(&stage.time.as_secs().to_string());
+                if stage.increment > Duration::new(0, 0) {
+                    field.push_str(&format!("+{}", stage.increment.as_secs()));
+                }
+                field
+            })
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stages() {
+        let settings = ClockSettings::from_pgn("40/5400+30:1800+30").unwrap();
+        assert_eq!(
+            settings.stages,
+            vec![
+                Stage {
+                    moves: Some(40),
+                    increment: Duration::from_secs(30),
+                    ..Stage::new(Duration::from_secs(5400))
+                },
+                Stage {
+                    increment: Duration::from_secs(30),
+                    ..Stage::new(Duration::from_secs(1800))
+                },
+            ]
+        );
+        assert!(!settings.hourglass);
+
+        let settings = ClockSettings::from_pgn("*180").unwrap();
+        assert_eq!(settings.stages, vec![Stage::new(Duration::from_secs(180))]);
+        assert!(settings.hourglass);
+    }
+
+    #[test]
+    fn round_trips() {
+        for input in &["40/5400+30:1800+30", "300+3", "600", "*180"] {
+            let settings = ClockSettings::from_pgn(input).unwrap();
+            assert_eq!(settings.to_pgn(), *input);
+        }
+        assert_eq!(
+            ClockSettings::from_pgn(" 300+3 ").unwrap().to_pgn(),
+            "300+3"
+        );
+    }
+
+    #[test]
+    fn points_at_errors() {
+        let error = |input| ClockSettings::from_pgn(input).unwrap_err();
+        assert_eq!(error("?").message, "unknown time control");
+        assert_eq!(error("-").message, "no time control");
+        assert_eq!(error("").message, "empty time control");
+        assert_eq!(error("300:40/60").snippet(), "300");
+        assert_eq!(error("0/60").snippet(), "0");
+        assert_eq!(error("*60:60").snippet(), "*60");
+        assert_eq!(error("300+x").snippet(), "x");
+        let huge = error("99999999999+3");
+        assert_eq!(huge.snippet(), "99999999999");
+        assert_eq!(huge.message, "too long the seconds");
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    collections::hash_map::RandomState,
+    convert::TryFrom,
+    fmt::Write,
+    hash::{BuildHasher, Hasher},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A salted SHA-256 hash of a PIN, written as `sha256$<salt>$<digest>` in hex so that the PIN
+/// itself is never stored.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PinHash {
+    salt: String,
+    digest: String,
+}
+
+impl PinHash {
+    pub fn new(pin: &str) -> Self {
+        // `RandomState` is seeded randomly by the OS, which is enough for a salt.
+        let salt = (0..2).fold(String::new(), |mut salt, _| {
+            let _ = write!(salt, "{:016x}", RandomState::new().build_hasher().finish());
+            salt
+        });
+        let digest = digest(&salt, pin);
+        Self { salt, digest }
+    }
+
+    pub fn verify(&self, pin: &str) -> bool {
+        let digest = digest(&self.salt, pin);
+        // Compares every byte so that the time taken doesn't tell how much of the digest matches.
+        digest.len() == self.digest.len()
+            && digest
+                .bytes()
+                .zip(self.digest.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+fn digest(salt: &str, pin: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(pin.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+impl TryFrom<String> for PinHash {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut parts = value.splitn(3, '$');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("sha256"), Some(salt), Some(digest))
+                if !salt.is_empty()
+                    && digest.len() == 64
+                    && digest.bytes().all(|b| b.is_ascii_hexdigit()) =>
+            {
+                Ok(Self {
+                    salt: salt.to_string(),
+                    digest: digest.to_ascii_lowercase(),
+                })
+            }
+            _ => Err("invalid PIN hash, expected sha256$<salt>$<digest>".to_string()),
+        }
+    }
+}
+
+impl From<PinHash> for String {
+    fn from(hash: PinHash) -> Self {
+        format!("sha256${}${}", hash.salt, hash.digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_only_the_pin() {
+        let hash = PinHash::new("1234");
+        assert!(hash.verify("1234"));
+        assert!(!hash.verify("1235"));
+        assert!(!hash.verify(""));
+    }
+
+    #[test]
+    fn round_trips_through_a_string() {
+        let hash = PinHash::new("1234");
+        let parsed = PinHash::try_from(String::from(hash.clone())).unwrap();
+        assert_eq!(parsed, hash);
+        assert!(parsed.verify("1234"));
+        assert!(PinHash::try_from("1234".to_string()).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::{fs, time::Duration};
+
+use anyhow::anyhow;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use toml::Value;
+
+use super::{file::config_path, ClockSettings, Overtime, Stage};
+
+/// A named time control.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Preset {
+    pub name: String,
+    pub clock: ClockSettings,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct PresetFile {
+    #[serde(default)]
+    presets: Vec<Preset>,
+}
+
+impl Preset {
+    pub fn new(name: impl Into<String>, clock: ClockSettings) -> Self {
+        Self {
+            name: name.into(),
+            clock,
+        }
+    }
+
+    /// Returns the presets shipped with this app.
+    pub fn builtin() -> Vec<Self> {
+        let min = |min: u64| Duration::new(min * 60, 0);
+        let sec = |sec| Duration::new(sec, 0);
+        let fischer = |time, increment| ClockSettings {
+            stages: vec![Stage {
+                increment,
+                ..Stage::new(time)
+            }],
+            overtime: None,
+            hourglass: false,
+        };
+        vec![
+            Self::new("Bullet 1+0", fischer(min(1), sec(0))),
+            Self::new("Bullet 2+1", fischer(min(2), sec(1))),
+            Self::new("Blitz 3+0", fischer(min(3), sec(0))),
+            Self::new("Blitz 3+2", fischer(min(3), sec(2))),
+            Self::new("Blitz 5+3", fischer(min(5), sec(3))),
+            Self::new("Rapid 10+5", fischer(min(10), sec(5))),
+            Self::new("Rapid 15+10", fischer(min(15), sec(10))),
+            Self::new("Classical 90+30", fischer(min(90), sec(30))),
+            Self::new(
+                "FIDE 40/90+30, 30+30",
+                ClockSettings {
+                    stages: vec![
+                        Stage {
+                            moves: Some(40),
+                            increment: sec(30),
+                            ..Stage::new(min(90))
+                        },
+                        Stage {
+                            increment: sec(30),
+                            ..Stage::new(min(30))
+                        },
+                    ],
+                    overtime: None,
+                    hourglass: false,
+                },
+            ),
+            Self::new(
+                "US Chess G/60;d5",
+                ClockSettings {
+                    stages: vec![Stage {
+                        delay: sec(5),
+                        ..Stage::new(min(60))
+                    }],
+                    overtime: None,
+                    hourglass: false,
+                },
+            ),
+            Self::new(
+                "Go 60m + 5x30s byo-yomi",
+                ClockSettings {
+                    stages: vec![Stage::new(min(60))],
+                    overtime: Some(Overtime::ByoYomi {
+                        periods: 5,
+                        time: sec(30),
+                    }),
+                    hourglass: false,
+                },
+            ),
+            Self::new(
+                "Go 10m + 3x30s byo-yomi",
+                ClockSettings {
+                    stages: vec![Stage::new(min(10))],
+                    overtime: Some(Overtime::ByoYomi {
+                        periods: 3,
+                        time: sec(30),
+                    }),
+                    hourglass: false,
+                },
+            ),
+            Self::new(
+                "Go 60m + 25/10m Canadian",
+                ClockSettings {
+                    stages: vec![Stage::new(min(60))],
+                    overtime: Some(Overtime::Canadian {
+                        moves: 25,
+                        time: min(10),
+                    }),
+                    hourglass: false,
+                },
+            ),
+        ]
+    }
+
+    /// Loads the presets defined by the user, ignoring the file if it is missing or malformed.
+    pub fn load_user() -> Vec<Self> {
+        let path = match config_path("presets.toml") {
+            Some(path) if path.is_file() => path,
+            _ => {
+                debug!("There are no user presets.");
+                return Vec::new();
+            }
+        };
+        let file = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| Ok(toml::from_str::<PresetFile>(&file)?));
+        match file {
+            Ok(file) => {
+                info!(
+                    "{} presets have been loaded from {}.",
+                    file.presets.len(),
+                    path.display()
+                );
+                file.presets
+            }
+            Err(e) => {
+                warn!("Failed to load {} ({:#}).", path.display(), e);
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn save_user(presets: &[Self]) -> anyhow::Result<()> {
+        let path = config_path("presets.toml")
+            .ok_or_else(|| anyhow!("the config directory isn't available"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let value = Value::try_from(PresetFile {
+            presets: presets.to_vec(),
+        })?;
+        fs::write(&path, toml::to_string(&value)?)?;
+        info!(
+            "{} presets have been saved to {}.",
+            presets.len(),
+            path.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,408 @@
+//! The shorthand notations people write time controls in, e.g. `5+3`, `G/60;d5`,
+//! `40/90, SD/30+30` or `60m+5x30s`.
+//!
+//! A stage is `[moves/]time` or `SD/time` (also `G/time`), followed by any of `+increment`,
+//! `inc increment` and `d delay`, optionally separated by `;`. Stages are separated by `,`. The
+//! last stage may be followed by byo-yomi `+periodsxtime` or Canadian overtime `+moves/time`. A
+//! sandclock is written `*time`. Times are in minutes and increments, delays and byo-yomi periods
+//! are in seconds, unless a unit `h`, `m` (`min`) or `s` (`sec`) is given.
+
+use std::{ops::Range, time::Duration};
+
+use super::{
+    parse::{ParseError, MAX_TIME},
+    ClockSettings, Overtime, Stage,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unit {
+    Hours,
+    Minutes,
+    Seconds,
+}
+
+impl Unit {
+    fn seconds(self) -> f64 {
+        match self {
+            Unit::Hours => 60.0 * 60.0,
+            Unit::Minutes => 60.0,
+            Unit::Seconds => 1.0,
+        }
+    }
+}
+
+/// A number with an optional unit.
+struct Quantity {
+    value: f64,
+    whole: bool,
+    unit: Option<Unit>,
+    range: Range<usize>,
+}
+
+struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        self.skip_spaces();
+        self.pos == self.input.len()
+    }
+
+    /// Consumes `keyword` case-insensitively if the input continues with it.
+    fn eat(&mut self, keyword: &str) -> bool {
+        self.skip_spaces();
+        let rest = self.rest();
+        if rest.len() >= keyword.len()
+            && rest.is_char_boundary(keyword.len())
+            && rest[..keyword.len()].eq_ignore_ascii_case(keyword)
+        {
+            self.pos += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, range: Range<usize>, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.input, range, message)
+    }
+
+    /// Returns an error about the next character, or the end of the input.
+    fn unexpected(&mut self, expected: &str) -> ParseError {
+        self.skip_spaces();
+        match self.rest().chars().next() {
+            Some(c) => self.error(
+                self.pos..self.pos + c.len_utf8(),
+                format!("expected {}", expected),
+            ),
+            None => self.error(self.pos..self.pos, format!("missing {}", expected)),
+        }
+    }
+
+    fn quantity(&mut self, what: &str) -> Result<Quantity, ParseError> {
+        self.skip_spaces();
+        let start = self.pos;
+        let rest = self.rest();
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(self.unexpected(what));
+        }
+        let number = &rest[..digits];
+        let value = number
+            .parse::<f64>()
+            .map_err(|_| self.error(start..start + digits, format!("invalid {}", what)))?;
+        self.pos += digits;
+
+        // Longer units first so that `min` isn't taken for `m` followed by garbage.
+        let units = [
+            ("hours", Unit::Hours),
+            ("hour", Unit::Hours),
+            ("h", Unit::Hours),
+            ("min", Unit::Minutes),
+            ("m", Unit::Minutes),
+            ("sec", Unit::Seconds),
+            ("s", Unit::Seconds),
+        ];
+        let unit = units
+            .iter()
+            .find(|(name, _)| {
+                let rest = self.rest();
+                rest.len() >= name.len()
+                    && rest.is_char_boundary(name.len())
+                    && rest[..name.len()].eq_ignore_ascii_case(name)
+            })
+            .map(|&(name, unit)| {
+                self.pos += name.len();
+                unit
+            });
+        Ok(Quantity {
+            value,
+            whole: !number.contains('.'),
+            unit,
+            range: start..self.pos,
+        })
+    }
+
+    fn duration(&mut self, default: Unit, what: &str) -> Result<Duration, ParseError> {
+        let quantity = self.quantity(what)?;
+        self.to_duration(&quantity, default, what)
+    }
+
+    /// Converts `quantity` to a duration, given in `default` if it has no unit, up to `MAX_TIME`.
+    fn to_duration(
+        &self,
+        quantity: &Quantity,
+        default: Unit,
+        what: &str,
+    ) -> Result<Duration, ParseError> {
+        Duration::try_from_secs_f64(quantity.value * quantity.unit.unwrap_or(default).seconds())
+            .ok()
+            .filter(|&duration| duration <= MAX_TIME)
+            .ok_or_else(|| self.error(quantity.range.clone(), format!("too long {}", what)))
+    }
+
+    fn count(&self, quantity: &Quantity, what: &str) -> Result<u32, ParseError> {
+        if !quantity.whole || quantity.unit.is_some() {
+            return Err(self.error(
+                quantity.range.clone(),
+                format!("expected {} as a whole number", what),
+            ));
+        }
+        if quantity.value < 1.0 || quantity.value > f64::from(u32::MAX) {
+            return Err(self.error(
+                quantity.range.clone(),
+                format!("{} must be between 1 and {}", what, u32::MAX),
+            ));
+        }
+        Ok(quantity.value as u32)
+    }
+}
+
+fn stage(scanner: &mut Scanner) -> Result<(Stage, Option<Overtime>), ParseError> {
+    let mut stage = Stage::new(Duration::new(0, 0));
+    let mut overtime = None;
+
+    if scanner.eat("sd/") || scanner.eat("g/") {
+        stage.time = scanner.duration(Unit::Minutes, "the time")?;
+    } else {
+        let quantity = scanner.quantity("the time or the number of moves")?;
+        if scanner.eat("/") {
+            stage.moves = Some(scanner.count(&quantity, "the number of moves")?);
+            stage.time = scanner.duration(Unit::Minutes, "the time of the stage")?;
+        } else {
+            stage.time = scanner.to_duration(&quantity, Unit::Minutes, "the time")?;
+        }
+    }
+
+    loop {
+        if scanner.eat(";") {
+            continue;
+        }
+        if scanner.eat("+") {
+            let quantity = scanner.quantity("the increment or the overtime")?;
+            if scanner.eat("x") {
+                let periods = scanner.count(&quantity, "the number of byo-yomi periods")?;
+                let time = scanner.duration(Unit::Seconds, "the time of a byo-yomi period")?;
+                overtime = Some(Overtime::ByoYomi { periods, time });
+            } else if scanner.eat("/") {
+                let moves = scanner.count(&quantity, "the number of moves of a block")?;
+                let time = scanner.duration(Unit::Minutes, "the time of a block")?;
+                overtime = Some(Overtime::Canadian { moves, time });
+            } else {
+                stage.increment = scanner.to_duration(&quantity, Unit::Seconds, "the increment")?;
+            }
+        } else if scanner.eat("inc") {
+            stage.increment = scanner.duration(Unit::Seconds, "the increment")?;
+        } else if scanner.eat("d") {
+            stage.delay = scanner.duration(Unit::Seconds, "the delay")?;
+        } else {
+            return Ok((stage, overtime));
+        }
+    }
+}
+
+impl ClockSettings {
+    /// Parses a time control written in shorthand.
+    pub fn from_shorthand(input: &str) -> Result<Self, ParseError> {
+        let mut scanner = Scanner::new(input);
+        let mut settings = ClockSettings {
+            stages: Vec::new(),
+            overtime: None,
+            hourglass: false,
+        };
+
+        if scanner.eat("*") {
+            settings
+                .stages
+                .push(Stage::new(scanner.duration(Unit::Minutes, "the time")?));
+            settings.hourglass = true;
+            if !scanner.is_at_end() {
+                return Err(scanner.unexpected("the end after a sandclock"));
+            }
+            return Ok(settings);
+        }
+
+        loop {
+            if scanner.is_at_end() {
+                return Err(scanner.unexpected("a time control"));
+            }
+            let start = scanner.pos;
+            let (stage, overtime) = stage(&mut scanner)?;
+            let range = start..scanner.pos;
+            if settings.overtime.is_some() {
+                return Err(scanner.error(range, "no stage may follow the overtime"));
+            }
+            let sudden_death = stage.moves.is_none();
+            settings.stages.push(stage);
+            settings.overtime = overtime;
+
+            if scanner.is_at_end() {
+                return Ok(settings);
+            }
+            if !scanner.eat(",") {
+                return Err(scanner.unexpected("',' before the next stage"));
+            }
+            if sudden_death {
+                return Err(scanner.error(range, "only the last stage may be sudden death"));
+            }
+        }
+    }
+
+    /// Formats the settings in the shorthand notation `from_shorthand` accepts.
+    pub fn to_shorthand(&self) -> String {
+        if self.hourglass {
+            return format!("*{}", format_time(self.time_limit(), Unit::Minutes));
+        }
+        let zero = Duration::new(0, 0);
+        let mut shorthand = self
+            .stages
+            .iter()
+            .map(|stage| {
+                let mut field = match stage.moves {
+                    Some(moves) => format!("{}/", moves),
+                    None if self.stages.len() > 1 => "SD/".to_string(),
+                    None if stage.delay > zero => "G/".to_string(),
+                    None => String::new(),
+                };
+                field.push_str(&format_time(stage.time, Unit::Minutes));
+                if stage.increment > zero {
+                    field.push_str(&format!("+{}", format_time(stage.increment, Unit::Seconds)));
+                }
+                if stage.delay > zero {
+                    field.push_str(&format!(";d{}", format_time(stage.delay, Unit::Seconds)));
+                }
+                field
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.overtime {
+            Some(Overtime::ByoYomi { periods, time }) => {
+                shorthand.push_str(&format!(
+                    "+{}x{}",
+                    periods,
+                    format_time(time, Unit::Seconds)
+                ));
+            }
+            Some(Overtime::Canadian { moves, time }) => {
+                shorthand.push_str(&format!("+{}/{}", moves, format_time(time, Unit::Minutes)));
+            }
+            None => {}
+        }
+        shorthand
+    }
+}
+
+/// Formats `duration` in `default` if it is a whole number of it, or with a unit otherwise.
+fn format_time(duration: Duration, default: Unit) -> String {
+    let secs = duration.as_secs_f64();
+    if (secs / default.seconds()).fract() == 0.0 {
+        format!("{}", secs / default.seconds())
+    } else if secs.fract() == 0.0 && secs % 60.0 == 0.0 {
+        format!("{}m", secs / 60.0)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(input: &str) -> ClockSettings {
+        let settings = ClockSettings::from_shorthand(input).unwrap();
+        assert_eq!(settings.to_shorthand(), input);
+        assert_eq!(
+            ClockSettings::from_shorthand(&settings.to_shorthand()).unwrap(),
+            settings
+        );
+        settings
+    }
+
+    #[test]
+    fn round_trips_fischer() {
+        let settings = round_trip("5+3");
+        assert_eq!(
+            settings.stages,
+            vec![Stage {
+                increment: Duration::from_secs(3),
+                ..Stage::new(Duration::from_secs(5 * 60))
+            }]
+        );
+        assert_eq!(settings.overtime, None);
+    }
+
+    #[test]
+    fn round_trips_delay() {
+        let settings = round_trip("G/60;d5");
+        assert_eq!(
+            settings.stages,
+            vec![Stage {
+                delay: Duration::from_secs(5),
+                ..Stage::new(Duration::from_secs(60 * 60))
+            }]
+        );
+    }
+
+    #[test]
+    fn round_trips_stages() {
+        let settings = round_trip("40/90, SD/30+30");
+        assert_eq!(
+            settings.stages,
+            vec![
+                Stage {
+                    moves: Some(40),
+                    ..Stage::new(Duration::from_secs(90 * 60))
+                },
+                Stage {
+                    increment: Duration::from_secs(30),
+                    ..Stage::new(Duration::from_secs(30 * 60))
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_overtime() {
+        let settings = round_trip("60+5x30");
+        assert_eq!(
+            settings.overtime,
+            Some(Overtime::ByoYomi {
+                periods: 5,
+                time: Duration::from_secs(30),
+            })
+        );
+        round_trip("60+25/10");
+        round_trip("*3");
+    }
+
+    #[test]
+    fn rejects_huge_times() {
+        for input in &[
+            "99999999999999999999",
+            "5+99999999999999999999",
+            &"9".repeat(400),
+        ] {
+            let error = ClockSettings::from_shorthand(input).unwrap_err();
+            assert!(error.message.starts_with("too long"), "{}", error);
+        }
+        let error = ClockSettings::from_shorthand("40/99999999999999999999").unwrap_err();
+        assert_eq!(error.snippet(), "99999999999999999999");
+    }
+}
@@ -0,0 +1,128 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::{Rgb, Settings};
+
+/// The colors of all the panes.
+///
+/// A user theme only needs the colors differing from the light theme.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Theme {
+    pub name: String,
+    pub background: Rgb,
+    pub text: Rgb,
+    pub button: Rgb,
+    pub button_text: Rgb,
+    /// The borders of the text inputs.
+    pub border: Rgb,
+    /// The time of the player to move.
+    pub active: Rgb,
+    /// The background of the clock of the player to move.
+    pub highlight: Rgb,
+    /// The time of the other players.
+    pub waiting: Rgb,
+    /// The time left below a warning threshold.
+    pub warning: Rgb,
+    /// The time left below a critical threshold.
+    pub critical: Rgb,
+    /// A fallen flag.
+    pub flag: Rgb,
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            background: Rgb::WHITE,
+            text: Rgb::BLACK,
+            button: Rgb::new(0xdd, 0xdd, 0xdd),
+            button_text: Rgb::BLACK,
+            border: Rgb::new(0xaa, 0xaa, 0xaa),
+            active: Rgb::BLACK,
+            highlight: Rgb::new(0xdd, 0xee, 0xff),
+            waiting: Rgb::new(0x80, 0x80, 0x80),
+            warning: Rgb::new(0xcc, 0x99, 0x00),
+            critical: Rgb::new(0xdd, 0x00, 0x00),
+            flag: Rgb::new(0xdd, 0x00, 0x00),
+        }
+    }
+
+    /// A theme for dim rooms.
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".to_string(),
+            background: Rgb::new(0x1e, 0x1e, 0x1e),
+            text: Rgb::new(0xd0, 0xd0, 0xd0),
+            button: Rgb::new(0x3a, 0x3a, 0x3a),
+            button_text: Rgb::new(0xd0, 0xd0, 0xd0),
+            border: Rgb::new(0x55, 0x55, 0x55),
+            active: Rgb::new(0xf0, 0xf0, 0xf0),
+            highlight: Rgb::new(0x2a, 0x3b, 0x4d),
+            waiting: Rgb::new(0x70, 0x70, 0x70),
+            warning: Rgb::new(0xe0, 0xb0, 0x00),
+            critical: Rgb::new(0xff, 0x50, 0x50),
+            flag: Rgb::new(0xff, 0x50, 0x50),
+        }
+    }
+
+    /// White on black, with the low-time colors told apart without red and green.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast".to_string(),
+            background: Rgb::BLACK,
+            text: Rgb::WHITE,
+            button: Rgb::WHITE,
+            button_text: Rgb::BLACK,
+            border: Rgb::WHITE,
+            active: Rgb::WHITE,
+            highlight: Rgb::new(0x00, 0x72, 0xb2),
+            waiting: Rgb::new(0xbb, 0xbb, 0xbb),
+            warning: Rgb::new(0xf0, 0xe4, 0x42),
+            critical: Rgb::new(0xe6, 0x9f, 0x00),
+            flag: Rgb::new(0xe6, 0x9f, 0x00),
+        }
+    }
+
+    pub fn builtin() -> Vec<Self> {
+        vec![Self::light(), Self::dark(), Self::high_contrast()]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Settings {
+    /// Returns the user themes followed by the built-in ones whose names they don't take.
+    pub fn available_themes(&self) -> Vec<Theme> {
+        let mut themes = self.themes.clone();
+        for theme in Theme::builtin() {
+            if themes.iter().all(|user| user.name != theme.name) {
+                themes.push(theme);
+            }
+        }
+        themes
+    }
+
+    /// Returns the selected theme, falling back on the light one if it doesn't exist.
+    pub fn theme(&self) -> Theme {
+        let name = match &self.theme {
+            Some(name) => name,
+            None => return Theme::light(),
+        };
+        match self
+            .available_themes()
+            .into_iter()
+            .find(|theme| &theme.name == name)
+        {
+            Some(theme) => theme,
+            None => {
+                warn!("No theme is named {}; use the light one.", name);
+                Theme::light()
+            }
+        }
+    }
+}
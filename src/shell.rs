@@ -0,0 +1,244 @@
+//! The window of the app, run in place of the one of iced, which can neither hide the mouse
+//! cursor nor take a scale factor.
+
+use std::pin::Pin;
+
+use iced::{window::Mode, Application, Executor};
+use iced_futures::futures::{
+    channel::mpsc,
+    task::{Context, Poll},
+    Sink,
+};
+use iced_native::{window::Backend as _, Cache, MouseCursor, Runtime, Size, UserInterface};
+use iced_wgpu::{window::Backend, Renderer};
+use iced_winit::{
+    conversion,
+    winit::{
+        dpi::{LogicalSize, PhysicalSize},
+        event::{self, ModifiersState, WindowEvent},
+        event_loop::{ControlFlow, EventLoop, EventLoopProxy},
+        window::WindowBuilder,
+    },
+    Clipboard,
+};
+
+use crate::settings::WindowSettings;
+
+/// Opens the window of `A` as `settings` say and runs the app until the window is closed.
+///
+/// The interface is laid out at `settings.scale` rather than the scale factor of the screen when
+/// one is given, and the mouse cursor is hidden over the window in the kiosk mode.
+pub fn run<A: Application + 'static>(flags: A::Flags, settings: &WindowSettings) -> ! {
+    let kiosk = settings.kiosk;
+    let fixed_scale = settings.scale;
+
+    let event_loop = EventLoop::with_user_event();
+    let mut runtime = {
+        let executor = A::Executor::new().expect("Create executor");
+        Runtime::new(executor, Proxy(event_loop.create_proxy()))
+    };
+    let (mut app, command) = runtime.enter(|| A::new(flags));
+    runtime.spawn(command);
+    runtime.track(app.subscription());
+
+    let mut title = app.title();
+    let mut mode = app.mode();
+    let size = LogicalSize::new(f64::from(settings.width), f64::from(settings.height));
+    let builder = WindowBuilder::new()
+        .with_title(&title)
+        .with_resizable(!kiosk)
+        .with_decorations(settings.decorations && !kiosk)
+        .with_fullscreen(conversion::fullscreen(
+            event_loop.primary_monitor(),
+            winit_mode(mode),
+        ));
+    let builder = match fixed_scale {
+        Some(scale) => builder.with_inner_size(size.to_physical::<u32>(scale)),
+        None => builder.with_inner_size(size),
+    };
+    let window = builder.build(&event_loop).expect("Open window");
+    window.set_cursor_visible(!kiosk);
+
+    let mut physical = window.inner_size();
+    let mut scale = fixed_scale.unwrap_or_else(|| window.scale_factor());
+    let mut resized = false;
+
+    let clipboard = Clipboard::new(&window);
+    let (mut backend, mut renderer) = Backend::new(iced_wgpu::Settings::default());
+    let surface = backend.create_surface(&window);
+    let mut swap_chain = backend.create_swap_chain(&surface, physical.width, physical.height);
+
+    let interface = build_interface(&mut app, Cache::default(), &mut renderer, physical, scale);
+    let mut primitive = interface.draw(&mut renderer);
+    let mut cache = Some(interface.into_cache());
+    let mut events = Vec::new();
+    let mut external_messages = Vec::new();
+    let mut mouse_cursor = MouseCursor::OutOfBounds;
+    let mut modifiers = ModifiersState::default();
+    window.request_redraw();
+
+    event_loop.run(move |event, _, control_flow| match event {
+        event::Event::MainEventsCleared => {
+            if events.is_empty() && external_messages.is_empty() {
+                return;
+            }
+
+            let mut interface = build_interface(
+                &mut app,
+                cache.take().unwrap(),
+                &mut renderer,
+                physical,
+                scale,
+            );
+            for event in events.iter().cloned() {
+                runtime.broadcast(event);
+            }
+            let mut messages = interface.update(
+                events.drain(..),
+                clipboard
+                    .as_ref()
+                    .map(|clipboard| clipboard as &dyn iced_native::Clipboard),
+                &renderer,
+            );
+            messages.append(&mut external_messages);
+
+            if messages.is_empty() {
+                primitive = interface.draw(&mut renderer);
+                cache = Some(interface.into_cache());
+            } else {
+                // The interface borrows the app, so it's rebuilt once the messages are handled.
+                let cache_before = interface.into_cache();
+                for message in messages {
+                    let command = runtime.enter(|| app.update(message));
+                    runtime.spawn(command);
+                }
+                runtime.track(app.subscription());
+                let new_title = app.title();
+                if new_title != title {
+                    window.set_title(&new_title);
+                    title = new_title;
+                }
+                let new_mode = app.mode();
+                if new_mode != mode {
+                    window.set_fullscreen(conversion::fullscreen(
+                        window.current_monitor(),
+                        winit_mode(new_mode),
+                    ));
+                    mode = new_mode;
+                }
+
+                let interface =
+                    build_interface(&mut app, cache_before, &mut renderer, physical, scale);
+                primitive = interface.draw(&mut renderer);
+                cache = Some(interface.into_cache());
+            }
+            window.request_redraw();
+        }
+        event::Event::UserEvent(message) => external_messages.push(message),
+        event::Event::RedrawRequested(_) => {
+            if resized {
+                swap_chain = backend.create_swap_chain(&surface, physical.width, physical.height);
+                resized = false;
+            }
+            let new_mouse_cursor =
+                backend.draw(&mut renderer, &mut swap_chain, &primitive, scale, &[""; 0]);
+            // Setting an icon would show the cursor again on some platforms.
+            if !kiosk && new_mouse_cursor != mouse_cursor {
+                window.set_cursor_icon(conversion::mouse_cursor(new_mouse_cursor));
+                mouse_cursor = new_mouse_cursor;
+            }
+        }
+        event::Event::WindowEvent {
+            event: window_event,
+            ..
+        } => {
+            match window_event {
+                WindowEvent::Resized(size) => {
+                    physical = size;
+                    resized = true;
+                }
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    ref new_inner_size,
+                } => {
+                    physical = **new_inner_size;
+                    scale = fixed_scale.unwrap_or(scale_factor);
+                    resized = true;
+                }
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::ModifiersChanged(new_modifiers) => modifiers = new_modifiers,
+                // Cmd+Q quits as in any app on macOS.
+                #[cfg(target_os = "macos")]
+                WindowEvent::KeyboardInput {
+                    input:
+                        event::KeyboardInput {
+                            virtual_keycode: Some(event::VirtualKeyCode::Q),
+                            state: event::ElementState::Pressed,
+                            ..
+                        },
+                    ..
+                } if modifiers.logo() => *control_flow = ControlFlow::Exit,
+                _ => {}
+            }
+            if let Some(event) = conversion::window_event(&window_event, scale, modifiers) {
+                events.push(event);
+            }
+        }
+        _ => *control_flow = ControlFlow::Wait,
+    })
+}
+
+/// Lays out the view of `app` in a window of `size` physical pixels at `scale`.
+fn build_interface<'a, A: Application>(
+    app: &'a mut A,
+    cache: Cache,
+    renderer: &mut Renderer,
+    size: PhysicalSize<u32>,
+    scale: f64,
+) -> UserInterface<'a, A::Message, Renderer> {
+    let size = size.to_logical::<f64>(scale);
+    UserInterface::build(
+        app.view(),
+        Size::new(size.width.round() as f32, size.height.round() as f32),
+        cache,
+        renderer,
+    )
+}
+
+fn winit_mode(mode: Mode) -> iced_winit::Mode {
+    match mode {
+        Mode::Windowed => iced_winit::Mode::Windowed,
+        Mode::Fullscreen => iced_winit::Mode::Fullscreen,
+    }
+}
+
+/// Sends the messages of the commands and subscriptions to the event loop.
+struct Proxy<Message: 'static>(EventLoopProxy<Message>);
+
+impl<Message: 'static> Clone for Proxy<Message> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Message: 'static> Sink<Message> for Proxy<Message> {
+    type Error = mpsc::SendError;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, message: Message) -> Result<(), Self::Error> {
+        // The event loop is only gone once the app is closing.
+        let _ = self.0.send_event(message);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,221 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    game::{Game, GameState, OvertimeState, Player},
+    settings::SoundSettings,
+};
+
+use super::Sound;
+
+/// Watches the game for the moments to be heard.
+pub struct Alarm {
+    /// The player to move and their time left at the last update.
+    last: Option<(Player, Duration)>,
+    /// The number of presses at the last update, if any.
+    presses: Option<usize>,
+    /// The stage and overtime of each player at the last update.
+    periods: Vec<(usize, Option<OvertimeState>)>,
+    flagged: bool,
+}
+
+impl Alarm {
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            presses: None,
+            periods: Vec::new(),
+            flagged: false,
+        }
+    }
+
+    /// Returns the sounds due to what has happened since the last update.
+    pub fn update(&mut self, game: &Game, now: Instant, settings: &SoundSettings) -> Vec<Sound> {
+        let mut sounds = Vec::new();
+
+        // An undo removes presses, which isn't heard.
+        let presses = game.history().presses().len();
+        if matches!(self.presses, Some(last) if presses > last) {
+            sounds.push(if presses == 1 {
+                Sound::GameStart
+            } else {
+                Sound::Switch
+            });
+        }
+        self.presses = Some(presses);
+
+        let periods: Vec<_> = game
+            .players()
+            .iter()
+            .map(|&player| (game.stage(player), game.overtime(player)))
+            .collect();
+        if periods.len() == self.periods.len() {
+            for (&(stage, overtime), &(last_stage, last_overtime)) in
+                periods.iter().zip(self.periods.iter())
+            {
+                match (last_overtime, overtime) {
+                    (
+                        Some(OvertimeState::ByoYomi { periods: last }),
+                        Some(OvertimeState::ByoYomi { periods }),
+                    ) if periods < last => sounds.push(Sound::PeriodConsumed),
+                    (None, Some(_)) => sounds.push(Sound::StageChange),
+                    (None, None) if stage > last_stage => sounds.push(Sound::StageChange),
+                    _ => {}
+                }
+            }
+        }
+        self.periods = periods;
+
+        let flagged = game.history().flag_fall().is_some();
+        if flagged && !self.flagged {
+            sounds.push(Sound::Flag);
+        }
+        self.flagged = flagged;
+
+        let current = match game.state() {
+            GameState::Running(player) => player,
+            _ => {
+                self.last = None;
+                return sounds;
+            }
+        };
+        let remaining = game.remaining(current, now);
+        if let Some((player, last)) = self.last {
+            // A switch or an undo doesn't pass any threshold.
+            if player == current && remaining < last {
+                if settings
+                    .warnings
+                    .iter()
+                    .any(|&warning| remaining <= warning && warning < last)
+                {
+                    sounds.push(Sound::Warning);
+                } else if remaining < settings.countdown
+                    && whole_seconds(remaining) < whole_seconds(last)
+                {
+                    sounds.push(Sound::Tick);
+                }
+            }
+        }
+        self.last = Some((current, remaining));
+        sounds
+    }
+}
+
+/// Returns the number of seconds as shown by a countdown, which is rounded up.
+fn whole_seconds(duration: Duration) -> u64 {
+    duration.as_secs() + if duration.subsec_nanos() > 0 { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{Players, Settings};
+
+    fn game() -> Game {
+        Game::new(&Settings {
+            players: Players::Two,
+            ..Settings::default()
+        })
+    }
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn warns_at_thresholds_and_ticks_the_countdown() {
+        let start = Instant::now();
+        let settings = SoundSettings::default();
+        let mut game = game();
+        let mut alarm = Alarm::new();
+        assert_eq!(alarm.update(&game, start, &settings), vec![]);
+        game.press(Player::First, start);
+        assert_eq!(
+            alarm.update(&game, start, &settings),
+            vec![Sound::GameStart]
+        );
+
+        let mut heard = |after| alarm.update(&game, start + millis(after), &settings);
+        assert_eq!(heard(119_000), vec![]);
+        assert_eq!(heard(120_500), vec![Sound::Warning]);
+        assert_eq!(heard(121_000), vec![]);
+        assert_eq!(heard(150_500), vec![Sound::Warning]);
+        assert_eq!(heard(169_000), vec![]);
+        // The warning at 10 seconds replaces the tick.
+        assert_eq!(heard(170_000), vec![Sound::Warning]);
+        assert_eq!(heard(170_500), vec![]);
+        assert_eq!(heard(171_000), vec![Sound::Tick]);
+        assert_eq!(heard(171_999), vec![]);
+        assert_eq!(heard(172_000), vec![Sound::Tick]);
+        // Several seconds passed at once are ticked once.
+        assert_eq!(heard(175_500), vec![Sound::Tick]);
+    }
+
+    #[test]
+    fn stays_silent_on_a_switch_or_an_undo() {
+        let start = Instant::now();
+        let settings = SoundSettings::default();
+        let mut game = game();
+        let mut alarm = Alarm::new();
+        game.press(Player::First, start);
+        game.press(Player::Second, start + millis(1_000));
+        game.press(Player::First, start + millis(122_000));
+        assert_eq!(
+            alarm.update(&game, start + millis(122_000), &settings),
+            vec![]
+        );
+
+        // The first player is left 59 seconds, below the warning at a minute.
+        game.press(Player::Second, start + millis(123_000));
+        assert_eq!(
+            alarm.update(&game, start + millis(123_000), &settings),
+            vec![Sound::Switch]
+        );
+        assert_eq!(
+            alarm.update(&game, start + millis(124_000), &settings),
+            vec![]
+        );
+
+        game.undo(start + millis(125_000));
+        assert_eq!(
+            alarm.update(&game, start + millis(125_000), &settings),
+            vec![]
+        );
+        game.redo(start + millis(126_000));
+        assert_eq!(
+            alarm.update(&game, start + millis(126_000), &settings),
+            vec![Sound::Switch]
+        );
+    }
+
+    #[test]
+    fn buzzes_the_flag_fall_once() {
+        let start = Instant::now();
+        let settings = SoundSettings {
+            warnings: Vec::new(),
+            countdown: Duration::new(0, 0),
+            ..SoundSettings::default()
+        };
+        let mut game = game();
+        let mut alarm = Alarm::new();
+        game.press(Player::First, start);
+        alarm.update(&game, start, &settings);
+
+        game.tick(start + millis(181_000));
+        assert_eq!(
+            alarm.update(&game, start + millis(181_000), &settings),
+            vec![Sound::Flag]
+        );
+        assert_eq!(
+            alarm.update(&game, start + millis(182_000), &settings),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn rounds_the_countdown_up() {
+        assert_eq!(whole_seconds(Duration::new(0, 0)), 0);
+        assert_eq!(whole_seconds(millis(1)), 1);
+        assert_eq!(whole_seconds(millis(9_000)), 9);
+        assert_eq!(whole_seconds(millis(9_500)), 10);
+    }
+}
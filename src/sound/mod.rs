@@ -0,0 +1,192 @@
+use std::{io::Cursor, time::Duration};
+
+use log::{debug, info, warn};
+use rodio::{source::SineWave, Decoder, Device, Source};
+
+mod alarm;
+mod pack;
+mod voice;
+
+pub use alarm::Alarm;
+pub use pack::{Pack, MANIFEST};
+pub use voice::{Timekeeper, Voice};
+
+/// An event of the clock which can be heard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sound {
+    /// The first press has started the game.
+    GameStart,
+    /// A press has switched the clock.
+    Switch,
+    /// The time left has passed one of the warning thresholds.
+    Warning,
+    /// A second has passed in the final countdown.
+    Tick,
+    /// A player has run out of time.
+    Flag,
+    /// A byo-yomi period has run out.
+    PeriodConsumed,
+    /// A player has moved on to the next stage, or into the overtime.
+    StageChange,
+}
+
+impl Sound {
+    pub const ALL: [Sound; 7] = [
+        Sound::GameStart,
+        Sound::Switch,
+        Sound::Warning,
+        Sound::Tick,
+        Sound::Flag,
+        Sound::PeriodConsumed,
+        Sound::StageChange,
+    ];
+
+    /// Returns the name of the sound in the manifest of a sound pack.
+    pub fn name(self) -> &'static str {
+        match self {
+            Sound::GameStart => "game_start",
+            Sound::Switch => "switch",
+            Sound::Warning => "warning",
+            Sound::Tick => "tick",
+            Sound::Flag => "flag",
+            Sound::PeriodConsumed => "period_consumed",
+            Sound::StageChange => "stage_change",
+        }
+    }
+}
+
+/// Plays the sounds on the default output device, or nowhere if there is none.
+pub struct Audio {
+    device: Option<Device>,
+    pack: Pack,
+    voice: Voice,
+}
+
+impl Audio {
+    pub fn new(pack: Pack, voice: Voice) -> Self {
+        let device = rodio::default_output_device();
+        if device.is_none() {
+            info!("No audio output device is available; the sounds are muted.");
+        }
+        Self {
+            device,
+            pack,
+            voice,
+        }
+    }
+
+    pub fn set_pack(&mut self, pack: Pack) {
+        self.pack = pack;
+    }
+
+    pub fn set_voice(&mut self, voice: Voice) {
+        self.voice = voice;
+    }
+
+    /// Reads `number` aloud, or beeps if there is no recording of it: long for the tens and
+    /// short for the others.
+    pub fn speak(&self, number: u32, volume: f32) {
+        let device = match &self.device {
+            Some(device) => device,
+            None => {
+                debug!("Read {} on the null backend.", number);
+                return;
+            }
+        };
+        let volume = limit(volume);
+        if let Some(sample) = self.voice.sample(number) {
+            match Decoder::new(Cursor::new(sample)) {
+                Ok(decoder) => {
+                    rodio::play_raw(device, decoder.convert_samples().amplify(volume));
+                    return;
+                }
+                Err(e) => warn!("Failed to decode the sample of {}: {}", number, e),
+            }
+        }
+        let length = match number % 10 {
+            0 => 300,
+            _ => 100,
+        };
+        rodio::play_raw(
+            device,
+            beep(1000, Duration::from_millis(length)).amplify(volume),
+        );
+    }
+
+    /// Plays `sound` at `volume` between 0 and 1 without waiting for it to end.
+    pub fn play(&self, sound: Sound, volume: f32) {
+        let device = match &self.device {
+            Some(device) => device,
+            None => {
+                debug!("Play {:?} on the null backend.", sound);
+                return;
+            }
+        };
+        let volume = limit(volume * self.pack.volume(sound));
+        if volume == 0.0 {
+            return;
+        }
+        if let Some(file) = self.pack.file(sound) {
+            match Decoder::new(Cursor::new(file)) {
+                Ok(decoder) => {
+                    rodio::play_raw(device, decoder.convert_samples().amplify(volume));
+                    return;
+                }
+                Err(e) => warn!("Failed to decode the sound of {:?}: {}", sound, e),
+            }
+        }
+        match sound {
+            Sound::GameStart => rodio::play_raw(
+                device,
+                beep(660, Duration::from_millis(120))
+                    .mix(beep(990, Duration::from_millis(200)).delay(Duration::from_millis(120)))
+                    .amplify(volume),
+            ),
+            Sound::Switch => rodio::play_raw(
+                device,
+                beep(2000, Duration::from_millis(15)).amplify(volume * 0.5),
+            ),
+            Sound::Warning => rodio::play_raw(
+                device,
+                beep(880, Duration::from_millis(150))
+                    .mix(beep(880, Duration::from_millis(150)).delay(Duration::from_millis(250)))
+                    .amplify(volume),
+            ),
+            Sound::Tick => rodio::play_raw(
+                device,
+                beep(1320, Duration::from_millis(40)).amplify(volume),
+            ),
+            // Two close frequencies beat against each other, which sounds like a buzzer.
+            Sound::Flag => rodio::play_raw(
+                device,
+                beep(220, Duration::from_millis(1500))
+                    .mix(beep(233, Duration::from_millis(1500)))
+                    .amplify(volume * 0.5),
+            ),
+            Sound::PeriodConsumed => rodio::play_raw(
+                device,
+                beep(440, Duration::from_millis(400)).amplify(volume),
+            ),
+            Sound::StageChange => rodio::play_raw(
+                device,
+                beep(523, Duration::from_millis(150))
+                    .mix(beep(659, Duration::from_millis(150)).delay(Duration::from_millis(150)))
+                    .mix(beep(784, Duration::from_millis(250)).delay(Duration::from_millis(300)))
+                    .amplify(volume),
+            ),
+        }
+    }
+}
+
+/// Limits `volume` to between 0 and 1, silencing a NaN, which `clamp` would keep.
+fn limit(volume: f32) -> f32 {
+    if volume.is_nan() {
+        0.0
+    } else {
+        volume.clamp(0.0, 1.0)
+    }
+}
+
+fn beep(frequency: u32, duration: Duration) -> impl Source<Item = f32> + Send {
+    SineWave::new(frequency).take_duration(duration)
+}
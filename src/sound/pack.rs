@@ -0,0 +1,182 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context};
+use log::{info, warn};
+use rodio::Decoder;
+use serde::Deserialize;
+
+use crate::settings;
+
+use super::Sound;
+
+/// The name of the manifest in the directory of a sound pack.
+pub const MANIFEST: &str = "sounds.toml";
+
+/// An entry of the manifest, e.g.
+///
+/// ```toml
+/// [switch]
+/// volume = 0.0
+///
+/// [flag]
+/// file = "buzzer.ogg"
+/// volume = 1.0
+/// ```
+#[derive(Deserialize)]
+#[serde(default)]
+struct Entry {
+    /// The WAV, OGG, FLAC or MP3 file relative to the directory, or `None` for the built-in sound.
+    file: Option<PathBuf>,
+    /// The factor between 0 and 1 applied to the volume of the settings, where 0 silences the
+    /// sound.
+    #[serde(deserialize_with = "settings::deserialize_volume")]
+    volume: f32,
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Self {
+            file: None,
+            volume: 1.0,
+        }
+    }
+}
+
+/// The sounds chosen by the user for some events, the others falling back on the built-in ones.
+#[derive(Default)]
+pub struct Pack {
+    files: HashMap<Sound, Arc<[u8]>>,
+    volumes: HashMap<Sound, f32>,
+}
+
+impl Pack {
+    /// Loads the pack in `dir` described by its manifest.
+    ///
+    /// A file which can't be read or decoded is skipped with a warning.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(MANIFEST);
+        let manifest = parse(
+            &fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?,
+        )
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        let mut pack = Self::default();
+        for (sound, entry) in manifest {
+            pack.volumes.insert(sound, entry.volume);
+            if let Some(file) = entry.file {
+                let file = dir.join(file);
+                match read(&file) {
+                    Ok(data) => {
+                        pack.files.insert(sound, data);
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to load {} ({:#}); use the built-in sound.",
+                            file.display(),
+                            e
+                        );
+                    }
+                }
+            }
+        }
+        info!("The sound pack {} has been loaded.", dir.display());
+        Ok(pack)
+    }
+
+    /// Loads the pack in `dir` if any, falling back on the built-in sounds if it can't be loaded.
+    pub fn load_or_default(dir: Option<&Path>) -> Self {
+        match dir.map(Self::load) {
+            Some(Ok(pack)) => pack,
+            Some(Err(e)) => {
+                warn!("Failed to load the sound pack: {:#}", e);
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the encoded file to be played for `sound`, if any.
+    pub fn file(&self, sound: Sound) -> Option<Arc<[u8]>> {
+        self.files.get(&sound).cloned()
+    }
+
+    /// Returns the factor applied to the volume of `sound`.
+    pub fn volume(&self, sound: Sound) -> f32 {
+        self.volumes.get(&sound).copied().unwrap_or(1.0)
+    }
+}
+
+/// Parses a manifest into the entries of the sounds, which are named as `Sound::name` returns.
+fn parse(manifest: &str) -> anyhow::Result<HashMap<Sound, Entry>> {
+    toml::from_str::<HashMap<String, Entry>>(manifest)?
+        .into_iter()
+        .map(|(name, entry)| {
+            let sound = Sound::ALL
+                .iter()
+                .copied()
+                .find(|sound| sound.name() == name)
+                .ok_or_else(|| anyhow!("unknown sound \"{}\"", name))?;
+            Ok((sound, entry))
+        })
+        .collect()
+}
+
+/// Reads a sound file, checking that it can be decoded.
+fn read(path: &Path) -> anyhow::Result<Arc<[u8]>> {
+    let data: Arc<[u8]> = fs::read(path)?.into();
+    Decoder::new(Cursor::new(data.clone()))?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_manifest() {
+        let manifest = parse(
+            r#"
+            [switch]
+            volume = 0.0
+
+            [flag]
+            file = "buzzer.ogg"
+            volume = 0.5
+
+            [period_consumed]
+            file = "gong.wav"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.len(), 3);
+        assert_eq!(manifest[&Sound::Switch].file, None);
+        assert_eq!(manifest[&Sound::Switch].volume, 0.0);
+        assert_eq!(
+            manifest[&Sound::Flag].file.as_deref(),
+            Some(Path::new("buzzer.ogg"))
+        );
+        assert_eq!(manifest[&Sound::Flag].volume, 0.5);
+        assert_eq!(manifest[&Sound::PeriodConsumed].volume, 1.0);
+    }
+
+    #[test]
+    fn rejects_unknown_sounds() {
+        let error = parse("[swtich]\nvolume = 0.5\n").err().unwrap();
+        assert!(error.to_string().contains("swtich"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_volumes_out_of_range() {
+        for volume in &["-0.5", "1.5", "nan", "inf"] {
+            let manifest = format!("[switch]\nvolume = {}\n", volume);
+            assert!(parse(&manifest).is_err(), "{}", volume);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Cursor,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use log::{info, warn};
+use rodio::Decoder;
+
+use crate::{
+    game::{Game, GameState, OvertimeState, Player},
+    settings::{Overtime, VoiceSettings},
+};
+
+/// The recordings of numbers read during byo-yomi, named after them, e.g. `10.ogg` or `3.wav`.
+#[derive(Default)]
+pub struct Voice {
+    samples: HashMap<u32, Arc<[u8]>>,
+}
+
+impl Voice {
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut voice = Self::default();
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            let number = match path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u32>().ok())
+            {
+                Some(number) => number,
+                None => continue,
+            };
+            let data: Arc<[u8]> = match fs::read(&path) {
+                Ok(data) => data.into(),
+                Err(e) => {
+                    warn!("Failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            match Decoder::new(Cursor::new(data.clone())) {
+                Ok(_) => {
+                    voice.samples.insert(number, data);
+                }
+                Err(e) => warn!("Failed to decode {}: {}", path.display(), e),
+            }
+        }
+        info!(
+            "{} voice samples have been loaded from {}.",
+            voice.samples.len(),
+            dir.display()
+        );
+        Ok(voice)
+    }
+
+    /// Loads the samples in `dir` if any, falling back on beeps if they can't be loaded.
+    pub fn load_or_default(dir: Option<&Path>) -> Self {
+        match dir.map(Self::load) {
+            Some(Ok(voice)) => voice,
+            Some(Err(e)) => {
+                warn!("Failed to load the voice samples: {:#}", e);
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the recording of `number`, if any.
+    pub fn sample(&self, number: u32) -> Option<Arc<[u8]>> {
+        self.samples.get(&number).cloned()
+    }
+}
+
+/// Calls the seconds of a byo-yomi period as a Go timekeeper does: every ten seconds, and then
+/// each second of the last ones counted from one.
+///
+/// With periods of 30 seconds, "10, 20, 1, 2, ... 9" is read before the period runs out.
+pub struct Timekeeper {
+    /// The player in byo-yomi, their periods left, their moves and the time elapsed in the period
+    /// at the last update.
+    last: Option<(Player, u32, u32, Duration)>,
+}
+
+impl Timekeeper {
+    pub fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Returns the number to be read for the seconds passed since the last update, if any.
+    pub fn update(&mut self, game: &Game, now: Instant, settings: &VoiceSettings) -> Option<u32> {
+        let current = match game.state() {
+            GameState::Running(player) => player,
+            _ => {
+                self.last = None;
+                return None;
+            }
+        };
+        let (periods, time) = match (game.overtime(current), &game.clock_settings().overtime) {
+            (Some(OvertimeState::ByoYomi { periods }), Some(Overtime::ByoYomi { time, .. })) => {
+                (periods, *time)
+            }
+            _ => {
+                self.last = None;
+                return None;
+            }
+        };
+        let moves = game.moves(current);
+        let elapsed = time
+            .checked_sub(game.remaining(current, now))
+            .unwrap_or_else(|| Duration::new(0, 0));
+
+        let mut number = None;
+        if let Some((player, last_periods, last_moves, last_elapsed)) = self.last {
+            // A new period starts after a move or when one runs out, and nothing is read then.
+            if player == current && last_periods == periods && last_moves == moves {
+                number = reading(last_elapsed, elapsed, time, settings);
+            }
+        }
+        self.last = Some((current, periods, moves, elapsed));
+        number
+    }
+}
+
+/// Returns the number to be read for the seconds of a period of `time` completed after `last`
+/// and up to `elapsed`, the latest one if several are due.
+fn reading(
+    last: Duration,
+    elapsed: Duration,
+    time: Duration,
+    settings: &VoiceSettings,
+) -> Option<u32> {
+    let last_seconds = time
+        .checked_sub(settings.count)
+        .unwrap_or_else(|| Duration::new(0, 0))
+        .as_secs();
+    let every = settings.every.as_secs();
+    let mut number = None;
+    for second in last.as_secs() + 1..=elapsed.as_secs() {
+        if second >= time.as_secs() {
+            break;
+        }
+        if second > last_seconds {
+            number = Some((second - last_seconds) as u32);
+        } else if every > 0 && second % every == 0 {
+            number = Some(second as u32);
+        }
+    }
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{ClockSettings, Settings, Stage};
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn voice(every: u64, count: u64) -> VoiceSettings {
+        VoiceSettings {
+            every: secs(every),
+            count: secs(count),
+            ..VoiceSettings::default()
+        }
+    }
+
+    /// Returns the numbers read second by second through a period of `time` seconds.
+    fn readings(time: u64, settings: &VoiceSettings) -> Vec<u32> {
+        (1..=time)
+            .filter_map(|second| reading(secs(second - 1), secs(second), secs(time), settings))
+            .collect()
+    }
+
+    #[test]
+    fn reads_the_tens_and_then_counts_the_last_seconds() {
+        assert_eq!(
+            readings(30, &voice(10, 10)),
+            vec![10, 20, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn counts_every_second_of_a_short_period() {
+        assert_eq!(readings(5, &voice(10, 10)), vec![1, 2, 3, 4]);
+        assert_eq!(
+            readings(10, &voice(10, 10)),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn reads_no_tens_with_every_zero() {
+        assert_eq!(readings(30, &voice(0, 10)), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn reads_only_the_latest_of_several_seconds() {
+        let settings = voice(10, 10);
+        assert_eq!(reading(secs(0), secs(25), secs(30), &settings), Some(5));
+        assert_eq!(reading(secs(11), secs(19), secs(30), &settings), None);
+        assert_eq!(reading(secs(25), secs(40), secs(30), &settings), Some(9));
+    }
+
+    #[test]
+    fn reads_nothing_after_a_period_is_used_or_a_move() {
+        let start = Instant::now();
+        let mut game = Game::new(&Settings {
+            clock: ClockSettings {
+                stages: vec![Stage::new(secs(10))],
+                overtime: Some(Overtime::ByoYomi {
+                    periods: 2,
+                    time: secs(30),
+                }),
+                hourglass: false,
+            },
+            ..Settings::default()
+        });
+        let settings = voice(10, 10);
+        let mut timekeeper = Timekeeper::new();
+        let mut heard = |game: &mut Game, after| {
+            game.tick(start + secs(after));
+            timekeeper.update(game, start + secs(after), &settings)
+        };
+        game.press(Player::First, start);
+        assert_eq!(heard(&mut game, 9), None);
+        assert_eq!(heard(&mut game, 11), None);
+        assert_eq!(heard(&mut game, 20), Some(10));
+
+        // The first period runs out at 40 seconds.
+        assert_eq!(heard(&mut game, 41), None);
+        assert_eq!(heard(&mut game, 45), None);
+
+        // A move restarts the period.
+        game.press(Player::First, start + secs(45));
+        assert_eq!(heard(&mut game, 57), None);
+        assert_eq!(heard(&mut game, 58), None);
+        assert_eq!(heard(&mut game, 65), Some(20));
+    }
+}